@@ -12,7 +12,9 @@ binpath=$HOME/.local/bin
 cd olsync
 cargo build --release
 mkdir -p $binpath
-cp ./target/release/olsync $binpath/olsync
+cp ../target/release/olsync $binpath/olsync
+# olsync runs as daemon when invoked as olsyncd
+ln -sf $binpath/olsync $binpath/olsyncd
 cd ..
 
 # Install olsync-rs-socketio-client
@@ -0,0 +1,170 @@
+use anyhow::Result;
+use bytes::Bytes;
+use std::future::Future;
+
+use crate::overleaf_client::{
+    CompileOptions, CompileResult, EntityType, Folder, OutputFile, OverleafClient, ProjectDetails,
+    ProjectEvents, ProjectsList,
+};
+
+// Operations on remote projects used by repository synchronization. Implemented by
+// OverleafClient talking to Overleaf and by FakeOverleaf keeping projects in memory, so that
+// synchronization logic can be tested without live Overleaf.
+pub trait OverleafApi: Send + Sync {
+    // Base URL of Overleaf instance the client talks to.
+    fn instance_url(&self) -> &str;
+
+    // Fetch all projects.
+    fn get_all_projects(&self) -> impl Future<Output = Result<ProjectsList>> + Send;
+
+    // Fetch specified project info, i.e. its file tree.
+    fn get_project_details(&self, project_id: &str) -> Result<ProjectDetails>;
+
+    // Download specified project as zip.
+    fn download_project_zip(
+        &self,
+        project_id: String,
+    ) -> impl Future<Output = Result<Bytes>> + Send;
+
+    // Download text of a single doc of specified project.
+    fn download_doc(
+        &self,
+        project_id: &str,
+        doc_id: &str,
+    ) -> impl Future<Output = Result<String>> + Send;
+
+    // Download a single binary file of specified project.
+    fn download_file(
+        &self,
+        project_id: &str,
+        file_id: &str,
+    ) -> impl Future<Output = Result<Bytes>> + Send;
+
+    // Upload file to specified folder in remote project, replacing entity with the same name.
+    fn upload_file(
+        &self,
+        project_id: &str,
+        folder_id: &str,
+        file_name: String,
+        file: Vec<u8>,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    // Replace content of existing doc.
+    fn update_doc(
+        &self,
+        project_id: &str,
+        doc_id: &str,
+        content: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    // Create folder with given name inside of specified folder in remote project.
+    fn create_folder(
+        &self,
+        project_id: &str,
+        parent_folder_id: &str,
+        name: &str,
+    ) -> impl Future<Output = Result<Folder>> + Send;
+
+    // Delete doc, file or folder with given id from remote project.
+    fn delete_entity(
+        &self,
+        project_id: &str,
+        entity_type: EntityType,
+        entity_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    // Join specified project and listen for its changes made by collaborators.
+    fn follow_project(&self, project_id: &str) -> Result<ProjectEvents>;
+
+    // Compile specified project.
+    fn request_compile(
+        &self,
+        project_id: &str,
+        options: &CompileOptions,
+    ) -> impl Future<Output = Result<CompileResult>> + Send;
+
+    // Download output file produced by compilation.
+    fn download_output_file(
+        &self,
+        compile_result: &CompileResult,
+        output_file: &OutputFile,
+    ) -> impl Future<Output = Result<Bytes>> + Send;
+}
+
+impl OverleafApi for OverleafClient {
+    fn instance_url(&self) -> &str {
+        OverleafClient::instance_url(self)
+    }
+
+    async fn get_all_projects(&self) -> Result<ProjectsList> {
+        OverleafClient::get_all_projects(self).await
+    }
+
+    fn get_project_details(&self, project_id: &str) -> Result<ProjectDetails> {
+        OverleafClient::get_project_details(self, project_id)
+    }
+
+    async fn download_project_zip(&self, project_id: String) -> Result<Bytes> {
+        OverleafClient::download_project_zip(self, project_id).await
+    }
+
+    async fn download_doc(&self, project_id: &str, doc_id: &str) -> Result<String> {
+        OverleafClient::download_doc(self, project_id, doc_id).await
+    }
+
+    async fn download_file(&self, project_id: &str, file_id: &str) -> Result<Bytes> {
+        OverleafClient::download_file(self, project_id, file_id).await
+    }
+
+    async fn upload_file(
+        &self,
+        project_id: &str,
+        folder_id: &str,
+        file_name: String,
+        file: Vec<u8>,
+    ) -> Result<()> {
+        OverleafClient::upload_file(self, project_id, folder_id, file_name, file).await
+    }
+
+    async fn update_doc(&self, project_id: &str, doc_id: &str, content: &str) -> Result<()> {
+        OverleafClient::update_doc(self, project_id, doc_id, content).await
+    }
+
+    async fn create_folder(
+        &self,
+        project_id: &str,
+        parent_folder_id: &str,
+        name: &str,
+    ) -> Result<Folder> {
+        OverleafClient::create_folder(self, project_id, parent_folder_id, name).await
+    }
+
+    async fn delete_entity(
+        &self,
+        project_id: &str,
+        entity_type: EntityType,
+        entity_id: &str,
+    ) -> Result<()> {
+        OverleafClient::delete_entity(self, project_id, entity_type, entity_id).await
+    }
+
+    fn follow_project(&self, project_id: &str) -> Result<ProjectEvents> {
+        OverleafClient::follow_project(self, project_id)
+    }
+
+    async fn request_compile(
+        &self,
+        project_id: &str,
+        options: &CompileOptions,
+    ) -> Result<CompileResult> {
+        OverleafClient::request_compile(self, project_id, options).await
+    }
+
+    async fn download_output_file(
+        &self,
+        compile_result: &CompileResult,
+        output_file: &OutputFile,
+    ) -> Result<Bytes> {
+        OverleafClient::download_output_file(self, compile_result, output_file).await
+    }
+}
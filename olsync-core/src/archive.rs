@@ -0,0 +1,144 @@
+use crate::{
+    overleaf_client::{OverleafClient, Project},
+    progress::Progress,
+    repository::download_project,
+    utils::path_to_str,
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::BufReader,
+    path::Path,
+};
+
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestEntry {
+    pub name: String,
+    pub last_updated: Option<String>,
+    pub archive: String,
+    pub archived_at: String,
+}
+
+// Manifest of account archive, keeping track of the latest archive of every project.
+#[derive(Debug, Deserialize, Serialize, Default)]
+pub struct Manifest {
+    pub projects: BTreeMap<String, ManifestEntry>,
+}
+
+impl Manifest {
+    // Read manifest from archive directory. Missing manifest is treated as empty.
+    pub fn read(archive_dir: &Path) -> Result<Self> {
+        match File::open(archive_dir.join(MANIFEST_FILE_NAME)) {
+            Ok(f) => serde_json::from_reader(BufReader::new(f))
+                .context("Failed to deserialize archive manifest."),
+            Err(_) => Ok(Manifest::default()),
+        }
+    }
+
+    pub fn save(&self, archive_dir: &Path) -> Result<()> {
+        fs::write(
+            archive_dir.join(MANIFEST_FILE_NAME),
+            serde_json::to_string_pretty(self)?,
+        )
+        .context("Failed to save archive manifest.")
+    }
+
+    // Check whether the latest archive of the project is still up to date.
+    fn is_up_to_date(&self, project: &Project, archive_dir: &Path) -> bool {
+        self.projects.get(&project.id).is_some_and(|entry| {
+            project.last_updated.is_some()
+                && entry.last_updated == project.last_updated
+                && archive_dir.join(&entry.archive).exists()
+        })
+    }
+}
+
+// Project name usable as a file or directory name, i.e. without path separators.
+pub(crate) fn file_name(project_name: &str) -> String {
+    project_name.replace(['/', '\\'], "-")
+}
+
+// Name of the project archive (without extension), annotated with project id, since names of
+// projects are not unique, and with current time.
+fn archive_name(project: &Project) -> String {
+    format!(
+        "{}-{}-{}",
+        file_name(&project.name),
+        project.id,
+        Utc::now().format("%Y%m%d-%H%M%S")
+    )
+}
+
+// Download every project changed since its last archive into archive directory and update the
+// manifest. Returns numbers of downloaded and skipped projects.
+pub async fn archive_all_projects(
+    overleaf_client: &OverleafClient,
+    projects: Vec<Project>,
+    archive_dir: &Path,
+    progress: &dyn Progress,
+) -> Result<(usize, usize)> {
+    let mut manifest = Manifest::read(archive_dir)?;
+
+    let mut downloaded = 0;
+    let mut skipped = 0;
+    let mut failed = 0;
+
+    for project in projects {
+        if manifest.is_up_to_date(&project, archive_dir) {
+            info!("Project {} has not changed, skipping.", project.name);
+            skipped += 1;
+            continue;
+        }
+
+        let name = archive_name(&project);
+
+        match download_project(
+            overleaf_client,
+            &project.id,
+            archive_dir,
+            Some(name.clone()),
+            progress,
+        )
+        .await
+        {
+            Ok(()) => {
+                manifest.projects.insert(
+                    project.id.clone(),
+                    ManifestEntry {
+                        name: project.name.clone(),
+                        last_updated: project.last_updated.clone(),
+                        archive: format!("{name}.zip"),
+                        archived_at: Utc::now().to_rfc3339(),
+                    },
+                );
+
+                // Save manifest after every project, so that interrupted runs are not repeated.
+                manifest.save(archive_dir)?;
+                downloaded += 1;
+            }
+            Err(err) => {
+                error!("Failed to archive project {}: {err}", project.name);
+                failed += 1;
+            }
+        }
+    }
+
+    manifest.save(archive_dir)?;
+
+    if failed > 0 {
+        bail!(
+            "Failed to archive {failed} projects. Archives are stored in {}.",
+            path_to_str(archive_dir)
+        )
+    }
+
+    Ok((downloaded, skipped))
+}
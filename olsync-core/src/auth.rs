@@ -11,12 +11,15 @@ use std::{
 };
 
 use crate::{
+    config::current_settings,
     constants::{
         GCLB_COOKIE_NAME, LOGIN_URL, ONE_HOUR_IN_SECONDS, SESSION_COOKIE_NAME, SOCKET_URL,
     },
+    http_trace::SendTraced,
     overleaf_client::{OlCookie, SessionInfo},
-    success,
-    utils::path_to_str,
+    paths::{migrate_legacy_file, state_dir},
+    progress::{Progress, ProgressEvent},
+    utils::{overleaf_url, path_to_str},
 };
 
 // Request GCLB cookie.
@@ -37,15 +40,14 @@ async fn get_gclb(session_cookie: OlCookie) -> Result<OlCookie> {
         .context("Failed to build reqwest client.")?;
 
     reqwest_client
-        .get(SOCKET_URL)
-        .send()
+        .get(overleaf_url(&current_settings().instance_url, SOCKET_URL))
+        .send_traced()
         .await?
         .headers()
         .get_all(SET_COOKIE)
         .iter()
         .filter_map(|val| val.to_str().ok().and_then(|s| Cookie::parse(s).ok()))
-        .filter(|cookie| cookie.name() == GCLB_COOKIE_NAME)
-        .last()
+        .rfind(|cookie| cookie.name() == GCLB_COOKIE_NAME)
         .map(|cookie| OlCookie {
             name: cookie.name().to_owned(),
             value: cookie.value().to_owned(),
@@ -56,14 +58,14 @@ async fn get_gclb(session_cookie: OlCookie) -> Result<OlCookie> {
         ))
 }
 
-pub async fn login() -> Result<SessionInfo> {
+pub async fn login(progress: &dyn Progress) -> Result<SessionInfo> {
     let launch_options = LaunchOptionsBuilder::default().headless(false).build()?;
 
     let browser = Browser::new(launch_options)?;
 
     let tab = browser.new_tab()?;
 
-    tab.navigate_to(LOGIN_URL)?;
+    tab.navigate_to(&overleaf_url(&current_settings().instance_url, LOGIN_URL))?;
 
     tab.wait_for_element_with_custom_timeout(
         "button#new-project-button-sidebar",
@@ -75,7 +77,7 @@ pub async fn login() -> Result<SessionInfo> {
         .get_attribute_value("content")?
         .context("User email meta tag content attribute is empty")?;
 
-    success!("Obtained user email.");
+    progress.report(ProgressEvent::Finished("Obtained user email.".to_owned()));
 
     let session_cookie = tab
         .get_cookies()?
@@ -85,18 +87,20 @@ pub async fn login() -> Result<SessionInfo> {
         .cloned()
         .map(OlCookie::from_chrome_cookie)?;
 
-    success!("Obtained session Cookie.");
+    progress.report(ProgressEvent::Finished(
+        "Obtained session Cookie.".to_owned(),
+    ));
 
     let csrf_token = tab
         .wait_for_element("meta[name=\"ol-csrfToken\"]")?
         .get_attribute_value("content")?
         .context("CSRF meta tag content attribute is empty.")?;
 
-    success!("Obtained CSRF Token.");
+    progress.report(ProgressEvent::Finished("Obtained CSRF Token.".to_owned()));
 
     let gclb_cookie = get_gclb(session_cookie.clone()).await?;
 
-    success!("Obtained GCLB Cookie.");
+    progress.report(ProgressEvent::Finished("Obtained GCLB Cookie.".to_owned()));
 
     Ok(SessionInfo {
         email,
@@ -106,16 +110,27 @@ pub async fn login() -> Result<SessionInfo> {
     })
 }
 
-// Get PathBuf pointing to ~/.olsyncinfo (it may not exist)
-fn get_olsyncinfo_path() -> Result<PathBuf> {
-    dirs::home_dir()
-        .map(|p| p.join(".olsyncinfo"))
-        .context("Failed to retrieve home directory.")
+// Get PathBuf pointing to session.json in state directory, or to session-{profile}.json if
+// profile is configured (it may not exist). Session cached in ~/.olsyncinfo by older versions
+// is moved there.
+fn get_session_info_path() -> Result<PathBuf> {
+    let (file_name, legacy_name) = match &current_settings().profile {
+        Some(profile) => (
+            format!("session-{profile}.json"),
+            format!(".olsyncinfo-{profile}"),
+        ),
+        None => ("session.json".to_owned(), ".olsyncinfo".to_owned()),
+    };
+
+    let info_path = state_dir()?.join(file_name);
+    migrate_legacy_file(&legacy_name, &info_path)?;
+
+    Ok(info_path)
 }
 
-// Try to retrieve cached session info from ~/.olsyncinfo.
+// Try to retrieve cached session info from state directory.
 pub fn get_session_info_from_file() -> Option<SessionInfo> {
-    let info_path = get_olsyncinfo_path().ok()?;
+    let info_path = get_session_info_path().ok()?;
 
     match File::open(info_path) {
         Ok(f) => serde_json::from_reader(BufReader::new(f))
@@ -125,18 +140,22 @@ pub fn get_session_info_from_file() -> Option<SessionInfo> {
     }
 }
 
-// Remove ~/.olsyncinfo file.
+// Remove cached session info.
 pub fn remove_session_info() -> Result<()> {
-    let info_path = get_olsyncinfo_path()?;
+    let info_path = get_session_info_path()?;
     fs::remove_file(info_path).map_err(|e| anyhow!("Failed to remove session info with error: {e}"))
 }
 
-// Save session info to ~/.olsyncinfo.
+// Save session info to state directory.
 fn save_session_info_to_file(session_info: &SessionInfo) -> Result<()> {
     info!("Saving session information to cache.");
 
     let serialized_info = serde_json::to_string(session_info)?;
-    let info_path = get_olsyncinfo_path()?;
+    let info_path = get_session_info_path()?;
+
+    if let Some(parent) = info_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
 
     fs::write(info_path.clone(), serialized_info).or_else(|_| {
         bail!(
@@ -147,30 +166,34 @@ fn save_session_info_to_file(session_info: &SessionInfo) -> Result<()> {
 }
 
 // Opens browser to log in and obtain new session information and saves it to cache.
-pub async fn get_session_info_from_browser() -> Result<SessionInfo> {
-    let session_info = login()
+pub async fn get_session_info_from_browser(progress: &dyn Progress) -> Result<SessionInfo> {
+    let session_info = login(progress)
         .await
         .context("Failed to obtain session info from login browser")?;
 
-    success!("Successfuly created new session.");
+    progress.report(ProgressEvent::Finished(
+        "Successfuly created new session.".to_owned(),
+    ));
 
     save_session_info_to_file(&session_info)?;
 
-    success!("Saved session info to cache.");
+    progress.report(ProgressEvent::Finished(
+        "Saved session info to cache.".to_owned(),
+    ));
 
     Ok(session_info)
 }
 
 // Read cached session info or spawn browser to login and
 // save new info in cache.
-pub async fn get_session_info() -> Result<SessionInfo> {
-    let session_info = get_session_info_from_file();
-
-    if session_info.is_none() {
-        warn!("Unable to detect cached session information. Opening browser for manual login.");
-        get_session_info_from_browser().await
+pub async fn get_session_info(progress: &dyn Progress) -> Result<SessionInfo> {
+    if let Some(session_info) = get_session_info_from_file() {
+        progress.report(ProgressEvent::Finished(
+            "Obtained session info from cache.".to_owned(),
+        ));
+        Ok(session_info)
     } else {
-        success!("Obtained session info from cache.");
-        Ok(session_info.unwrap())
+        warn!("Unable to detect cached session information. Opening browser for manual login.");
+        get_session_info_from_browser(progress).await
     }
 }
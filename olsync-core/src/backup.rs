@@ -0,0 +1,333 @@
+use crate::{
+    config::Settings,
+    progress::{Progress, ProgressEvent},
+    repository::{read_project_info, wipe_project},
+    utils::path_to_str,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{TimeDelta, Utc};
+use fs_extra::dir::CopyOptions;
+use log::info;
+use serde::Serialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const BACKUP_SUFFIX: &str = ".local.bak";
+
+// Backup of local state of repository, kept in .olsync directory as
+// {project}-{timestamp}.local.bak. Timestamp in milliseconds identifies the backup.
+#[derive(Debug, Clone, Serialize)]
+pub struct Backup {
+    pub id: String,
+    pub timestamp: i64,
+    pub size: u64,
+    pub path: PathBuf,
+}
+
+// Total size of files in directory, including subdirectories.
+fn dir_size(path: &Path) -> u64 {
+    fs::read_dir(path)
+        .map(|items| {
+            items
+                .filter_map(|item| item.ok())
+                .map(|item| match item.metadata() {
+                    Ok(metadata) if metadata.is_dir() => dir_size(&item.path()),
+                    Ok(metadata) => metadata.len(),
+                    Err(_) => 0,
+                })
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+// Parse age given as number with unit, e.g. 30d. Supported units are s, m, h, d and w.
+pub fn parse_age(age: &str) -> Result<TimeDelta> {
+    let invalid = || anyhow!("Invalid age {age}. Use number with unit s, m, h, d or w, e.g. 30d.");
+
+    let split = age
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (amount, unit) = age.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+
+    match unit {
+        "s" => TimeDelta::try_seconds(amount),
+        "m" => TimeDelta::try_minutes(amount),
+        "h" => TimeDelta::try_hours(amount),
+        "d" => TimeDelta::try_days(amount),
+        "w" => TimeDelta::try_weeks(amount),
+        _ => None,
+    }
+    .ok_or_else(invalid)
+}
+
+// List backups of repository in given root directory, the newest first.
+pub fn list_backups(repo_root: &Path) -> Result<Vec<Backup>> {
+    let mut backups: Vec<Backup> = fs::read_dir(repo_root.join(".olsync"))
+        .context("Failed to read .olsync directory.")?
+        .filter_map(|item| item.ok())
+        .filter_map(|item| {
+            let path = item.path();
+            let name = path.file_name()?.to_str()?.strip_suffix(BACKUP_SUFFIX)?;
+            let id = name.rsplit_once('-')?.1.to_owned();
+            let timestamp = id.parse().ok()?;
+
+            path.is_dir().then(|| Backup {
+                id,
+                timestamp,
+                size: dir_size(&path),
+                path,
+            })
+        })
+        .collect();
+
+    backups.sort_by_key(|backup| -backup.timestamp);
+
+    Ok(backups)
+}
+
+// Copy everything in root directory except .olsync into a timestamp annotated backup directory
+// and return its path.
+pub(crate) fn create_backup(repo_root: &Path) -> Result<PathBuf> {
+    let bak_name = &format!(
+        "{}-{}{BACKUP_SUFFIX}",
+        &read_project_info(repo_root)?.name,
+        Utc::now().timestamp_millis()
+    );
+    let bak_path = repo_root.join(".olsync").join(bak_name);
+
+    fs::create_dir(bak_path.clone())?;
+
+    copy_contents(repo_root, &bak_path)?;
+
+    Ok(bak_path)
+}
+
+// Copy everything in source directory except .olsync into target directory.
+fn copy_contents(source: &Path, target: &Path) -> Result<()> {
+    for item in fs::read_dir(source)? {
+        let path = item?.path();
+
+        if path.file_name().is_some_and(|name| name != ".olsync") {
+            fs_extra::copy_items(&[path_to_str(&path)], target, &CopyOptions::new())?;
+        }
+    }
+
+    Ok(())
+}
+
+// Remove backups, given the newest first, beyond the `keep` newest ones and backups older than
+// `max_age`. Returns the removed backups.
+fn remove_old_backups(
+    backups: Vec<Backup>,
+    keep: Option<usize>,
+    max_age: Option<TimeDelta>,
+) -> Result<Vec<Backup>> {
+    let now = Utc::now().timestamp_millis();
+    let mut removed = Vec::new();
+
+    for (index, backup) in backups.into_iter().enumerate() {
+        let too_many = keep.is_some_and(|keep| index >= keep);
+        let too_old =
+            max_age.is_some_and(|max_age| now - backup.timestamp > max_age.num_milliseconds());
+
+        if too_many || too_old {
+            fs::remove_dir_all(&backup.path).context(format!(
+                "Failed to remove backup {}.",
+                path_to_str(&backup.path)
+            ))?;
+
+            removed.push(backup);
+        }
+    }
+
+    Ok(removed)
+}
+
+// Remove backups of repository beyond the `keep` newest ones and backups older than `max_age`.
+// Returns the removed backups.
+pub fn prune_backups(
+    repo_root: &Path,
+    keep: Option<usize>,
+    max_age: Option<TimeDelta>,
+) -> Result<Vec<Backup>> {
+    remove_old_backups(list_backups(repo_root)?, keep, max_age)
+}
+
+// Prune backups according to retention limits in settings of repository. The newest backup is
+// always kept, since it has just been created, and so is the backup of given id that has just
+// been restored.
+pub(crate) fn apply_retention(repo_root: &Path, restored_id: Option<&str>) -> Result<()> {
+    let settings = Settings::load(Some(repo_root))?;
+
+    if settings.backup.keep.is_none() && settings.backup.max_age.is_none() {
+        return Ok(());
+    }
+
+    let keep = settings.backup.keep.map(|keep| keep.saturating_sub(1));
+    let max_age = settings
+        .backup
+        .max_age
+        .as_deref()
+        .map(parse_age)
+        .transpose()?;
+
+    let older_backups = list_backups(repo_root)?
+        .into_iter()
+        .skip(1)
+        .filter(|backup| Some(backup.id.as_str()) != restored_id)
+        .collect();
+    let removed = remove_old_backups(older_backups, keep, max_age)?;
+
+    if !removed.is_empty() {
+        info!("Removed {} backups beyond retention limits.", removed.len());
+    }
+
+    Ok(())
+}
+
+// Replace local state of repository with backup of given id. Current local state is backed up
+// first and path of that backup is returned.
+pub fn restore_backup(repo_root: &Path, id: &str, progress: &dyn Progress) -> Result<PathBuf> {
+    let backup = list_backups(repo_root)?
+        .into_iter()
+        .find(|backup| backup.id == id)
+        .ok_or_else(|| anyhow!("Backup {id} not found. Use olsync backup list to see backups."))?;
+
+    progress.report(ProgressEvent::Started(
+        "Creating backup of local project.".to_owned(),
+    ));
+
+    let safety_backup = create_backup(repo_root)?;
+
+    progress.report(ProgressEvent::Finished(format!(
+        "Saved backup of local project in {}.",
+        path_to_str(&safety_backup)
+    )));
+
+    progress.report(ProgressEvent::Started(format!("Restoring backup {id}.")));
+
+    let restore_result =
+        wipe_project(repo_root).and_then(|()| copy_contents(&backup.path, repo_root));
+
+    if let Err(err) = restore_result {
+        progress.report(ProgressEvent::Failed(format!(
+            "Failed to restore backup {id}."
+        )));
+        bail!(err)
+    }
+
+    progress.report(ProgressEvent::Finished(format!("Restored backup {id}.")));
+
+    apply_retention(repo_root, Some(id))?;
+
+    Ok(safety_backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{overleaf_client::Project, progress::NoProgress};
+    use tempfile::TempDir;
+
+    fn repository_with_backups(timestamps: &[i64]) -> (TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let repo_root = dir.path().to_path_buf();
+
+        fs::create_dir(repo_root.join(".olsync")).unwrap();
+        fs::write(
+            repo_root.join(".olsync").join("projectinfo"),
+            serde_json::to_string(&Project {
+                id: "thesis-id".to_owned(),
+                name: "thesis".to_owned(),
+                last_updated: None,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+
+        for timestamp in timestamps {
+            let path = repo_root
+                .join(".olsync")
+                .join(format!("thesis-{timestamp}{BACKUP_SUFFIX}"));
+
+            fs::create_dir(&path).unwrap();
+            fs::write(path.join("main.tex"), timestamp.to_string()).unwrap();
+        }
+
+        (dir, repo_root)
+    }
+
+    #[test]
+    fn ages_are_parsed_with_units() {
+        assert_eq!(parse_age("30d").unwrap(), TimeDelta::days(30));
+        assert_eq!(parse_age("2w").unwrap(), TimeDelta::weeks(2));
+        assert!(parse_age("30").is_err());
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("3y").is_err());
+    }
+
+    #[test]
+    fn prune_keeps_newest_backups() {
+        let now = Utc::now().timestamp_millis();
+        let day = TimeDelta::days(1).num_milliseconds();
+        let (_dir, repo_root) =
+            repository_with_backups(&[now - 40 * day, now - 2 * day, now - day, now]);
+
+        let removed = prune_backups(&repo_root, None, Some(TimeDelta::days(30))).unwrap();
+        assert_eq!(removed.len(), 1);
+
+        let removed = prune_backups(&repo_root, Some(2), None).unwrap();
+        assert_eq!(removed[0].id, (now - 2 * day).to_string());
+
+        let remaining: Vec<_> = list_backups(&repo_root)
+            .unwrap()
+            .into_iter()
+            .map(|backup| backup.timestamp)
+            .collect();
+        assert_eq!(remaining, vec![now, now - day]);
+    }
+
+    #[test]
+    fn restore_backs_up_current_state_first() {
+        let (_dir, repo_root) = repository_with_backups(&[1000]);
+        fs::write(repo_root.join("main.tex"), "current").unwrap();
+        fs::write(repo_root.join("notes.txt"), "current").unwrap();
+
+        let safety_backup = restore_backup(&repo_root, "1000", &NoProgress).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(repo_root.join("main.tex")).unwrap(),
+            "1000"
+        );
+        assert!(!repo_root.join("notes.txt").exists());
+        assert_eq!(
+            fs::read_to_string(safety_backup.join("notes.txt")).unwrap(),
+            "current"
+        );
+        assert!(restore_backup(&repo_root, "2000", &NoProgress).is_err());
+    }
+
+    #[test]
+    fn restored_backup_is_kept_by_retention() {
+        let (_dir, repo_root) = repository_with_backups(&[1000, 2000]);
+        fs::write(
+            repo_root.join(".olsync").join("config"),
+            "[backup]\nkeep = 1\n",
+        )
+        .unwrap();
+
+        restore_backup(&repo_root, "1000", &NoProgress).unwrap();
+
+        let remaining: Vec<_> = list_backups(&repo_root)
+            .unwrap()
+            .into_iter()
+            .map(|backup| backup.id)
+            .collect();
+        assert_eq!(remaining.len(), 2);
+        assert!(remaining.contains(&"1000".to_owned()));
+    }
+}
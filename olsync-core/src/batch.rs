@@ -0,0 +1,324 @@
+use crate::{
+    archive::file_name,
+    config::{current_settings, Settings},
+    overleaf_client::{CompileOptions, OverleafClient, Project},
+    progress::{Progress, ProgressEvent},
+    repository::{
+        backup_project, check_project_zip, extract_project, init_olsync_repository,
+        read_project_info, wipe_project,
+    },
+    utils::path_to_str,
+};
+
+use anyhow::{bail, Context, Result};
+use futures_util::{stream, StreamExt};
+use globset::Glob;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BatchOperation {
+    Clone,
+    Pull,
+    DownloadZip,
+    Compile,
+}
+
+impl BatchOperation {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "clone" => Some(BatchOperation::Clone),
+            "pull" => Some(BatchOperation::Pull),
+            "download-zip" => Some(BatchOperation::DownloadZip),
+            "compile" => Some(BatchOperation::Compile),
+            _ => None,
+        }
+    }
+}
+
+// Filter selecting projects for batch operations. Projects have to match all given criteria.
+#[derive(Debug, Default)]
+pub struct ProjectFilter {
+    pub tag: Option<String>,
+    pub name_pattern: Option<String>,
+    pub ids: Option<Vec<String>>,
+}
+
+pub struct BatchResult {
+    pub project: Project,
+    pub result: Result<String>,
+}
+
+// Read project ids from file, one per line. Empty lines and lines starting with # are skipped.
+pub fn read_ids_file(path: &Path) -> Result<Vec<String>> {
+    Ok(fs::read_to_string(path)
+        .context(format!("Failed to read ids file {}.", path_to_str(path)))?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_owned)
+        .collect())
+}
+
+// Fetch remote projects matching the filter.
+pub async fn select_projects(
+    overleaf_client: &OverleafClient,
+    filter: &ProjectFilter,
+) -> Result<Vec<Project>> {
+    let mut projects = overleaf_client.get_all_projects().await?.projects;
+
+    if let Some(tag_name) = &filter.tag {
+        let tag = overleaf_client
+            .get_all_tags()
+            .await?
+            .into_iter()
+            .find(|tag| tag.name == *tag_name)
+            .context(format!("Tag {tag_name} not found."))?;
+
+        projects.retain(|project| tag.project_ids.contains(&project.id));
+    }
+
+    if let Some(pattern) = &filter.name_pattern {
+        let matcher = Glob::new(pattern)
+            .context(format!("Invalid project name pattern {pattern}."))?
+            .compile_matcher();
+
+        projects.retain(|project| matcher.is_match(&project.name));
+    }
+
+    if let Some(ids) = &filter.ids {
+        projects.retain(|project| ids.contains(&project.id));
+    }
+
+    Ok(projects)
+}
+
+// Names of files and directories of projects in target directory, by project id. Path
+// separators in names are replaced and projects sharing a name get their id appended, so that
+// they do not overwrite each other.
+fn target_names(projects: &[Project]) -> HashMap<String, String> {
+    let names: Vec<String> = projects
+        .iter()
+        .map(|project| file_name(&project.name))
+        .collect();
+
+    let mut name_counts: HashMap<&str, usize> = HashMap::new();
+
+    for name in &names {
+        *name_counts.entry(name).or_default() += 1;
+    }
+
+    projects
+        .iter()
+        .zip(&names)
+        .map(|(project, name)| {
+            let name = if name_counts[name.as_str()] > 1 {
+                format!("{name}-{}", project.id)
+            } else {
+                name.clone()
+            };
+
+            (project.id.clone(), name)
+        })
+        .collect()
+}
+
+// Run operation for a single project and return description of the outcome. Files and
+// directories of the project in target directory are named `name`.
+async fn run_operation(
+    overleaf_client: &OverleafClient,
+    operation: BatchOperation,
+    project: &Project,
+    name: &str,
+    target_dir: &Path,
+    no_backup: bool,
+) -> Result<String> {
+    match operation {
+        BatchOperation::Clone => {
+            let repo_root = init_olsync_repository(project, &target_dir.join(name))?;
+
+            let archive = overleaf_client
+                .download_project_zip(project.id.clone())
+                .await?;
+
+            extract_project(archive.to_vec(), &repo_root)?;
+
+            Ok(format!("Cloned into {}.", path_to_str(&repo_root)))
+        }
+        BatchOperation::Pull => {
+            let repo_root = target_dir.join(name);
+
+            if read_project_info(&repo_root)?.id != project.id {
+                bail!(
+                    "Directory {} contains a different project.",
+                    path_to_str(&repo_root)
+                );
+            }
+
+            let settings = Settings::load(Some(&repo_root))?;
+            settings.check_instance(overleaf_client.instance_url())?;
+
+            let archive = overleaf_client
+                .download_project_zip(project.id.clone())
+                .await?;
+
+            check_project_zip(&archive)?;
+
+            if !no_backup && settings.backup.enabled {
+                backup_project(&repo_root)?;
+            }
+
+            wipe_project(&repo_root)?;
+            extract_project(archive.to_vec(), &repo_root)?;
+
+            Ok(format!("Pulled into {}.", path_to_str(&repo_root)))
+        }
+        BatchOperation::DownloadZip => {
+            let archive_path = target_dir.join(format!("{name}.zip"));
+
+            let archive = overleaf_client
+                .download_project_zip(project.id.clone())
+                .await?;
+
+            check_project_zip(&archive)?;
+
+            fs::write(&archive_path, archive).context("Failed to save downloaded project.")?;
+
+            Ok(format!("Saved as {}.", path_to_str(&archive_path)))
+        }
+        BatchOperation::Compile => {
+            let pdf_path = target_dir.join(format!("{name}.pdf"));
+
+            let options = CompileOptions {
+                root_doc_id: None,
+                draft: current_settings().compile.draft,
+                stop_on_first_error: current_settings().compile.stop_on_first_error,
+            };
+
+            let compile_result = overleaf_client
+                .request_compile(&project.id, &options)
+                .await?;
+
+            if !compile_result.is_success() {
+                bail!(
+                    "Compilation finished with status {}.",
+                    compile_result.status
+                );
+            }
+
+            let pdf = overleaf_client
+                .download_output_file(
+                    &compile_result,
+                    compile_result
+                        .output_file("output.pdf")
+                        .context("Compilation did not produce output.pdf.")?,
+                )
+                .await?;
+
+            fs::write(&pdf_path, pdf).context("Failed to save output.pdf.")?;
+
+            Ok(format!("Saved PDF as {}.", path_to_str(&pdf_path)))
+        }
+    }
+}
+
+// Run operation for all projects, with at most `jobs` projects processed at once.
+pub async fn run_batch(
+    overleaf_client: &OverleafClient,
+    operation: BatchOperation,
+    projects: Vec<Project>,
+    target_dir: &Path,
+    jobs: usize,
+    no_backup: bool,
+    progress: &dyn Progress,
+) -> Vec<BatchResult> {
+    let total = projects.len();
+    let target_dir = PathBuf::from(target_dir);
+    let names = target_names(&projects);
+
+    let mut results: Vec<BatchResult> = stream::iter(projects)
+        .map(|project| {
+            let target_dir = &target_dir;
+            let name = &names[&project.id];
+
+            async move {
+                let result = run_operation(
+                    overleaf_client,
+                    operation,
+                    &project,
+                    name,
+                    target_dir,
+                    no_backup,
+                )
+                .await;
+
+                BatchResult { project, result }
+            }
+        })
+        .buffer_unordered(jobs.max(1))
+        .enumerate()
+        .map(|(idx, batch_result)| {
+            progress.report(match &batch_result.result {
+                Ok(message) => ProgressEvent::Finished(format!(
+                    "[{}/{total}] {}: {message}",
+                    idx + 1,
+                    batch_result.project.name
+                )),
+                Err(err) => ProgressEvent::Failed(format!(
+                    "[{}/{total}] {}: {err}",
+                    idx + 1,
+                    batch_result.project.name
+                )),
+            });
+
+            batch_result
+        })
+        .collect()
+        .await;
+
+    results.sort_by(|a, b| a.project.name.cmp(&b.project.name));
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(id: &str, name: &str) -> Project {
+        Project {
+            id: id.to_owned(),
+            name: name.to_owned(),
+            last_updated: None,
+        }
+    }
+
+    #[test]
+    fn projects_sharing_name_get_distinct_targets() {
+        let names = target_names(&[
+            project("1", "thesis"),
+            project("2", "thesis"),
+            project("3", "notes"),
+        ]);
+
+        assert_eq!(names["1"], "thesis-1");
+        assert_eq!(names["2"], "thesis-2");
+        assert_eq!(names["3"], "notes");
+    }
+
+    #[test]
+    fn path_separators_in_names_are_replaced() {
+        let names = target_names(&[
+            project("1", "2024/hw1"),
+            project("2", "2024-hw1"),
+            project("3", "notes\\draft"),
+        ]);
+
+        assert_eq!(names["1"], "2024-hw1-1");
+        assert_eq!(names["2"], "2024-hw1-2");
+        assert_eq!(names["3"], "notes-draft");
+    }
+}
@@ -0,0 +1,344 @@
+use crate::{backup::parse_age, constants::BASE_URL, paths::config_dir};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+use toml::{Table, Value};
+use toml_edit::DocumentMut;
+
+// Keys which can be read and written with olsync config. Keys of sections are dotted.
+pub const CONFIG_KEYS: [&str; 12] = [
+    "instance_url",
+    "profile",
+    "confirm",
+    "main_document",
+    "remote_dir",
+    "ignore",
+    "backup.enabled",
+    "backup.keep",
+    "backup.max_age",
+    "compile.output",
+    "compile.draft",
+    "compile.stop_on_first_error",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackupSettings {
+    // Whether local state is backed up before it is replaced by pull or checkout.
+    pub enabled: bool,
+    // Number of the newest backups kept when a new one is created.
+    pub keep: Option<usize>,
+    // Age after which backups are removed when a new one is created, e.g. 30d.
+    pub max_age: Option<String>,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        BackupSettings {
+            enabled: true,
+            keep: None,
+            max_age: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompileSettings {
+    // Where compile saves the PDF, relative to repository root.
+    pub output: String,
+    pub draft: bool,
+    pub stop_on_first_error: bool,
+}
+
+impl Default for CompileSettings {
+    fn default() -> Self {
+        CompileSettings {
+            output: "output.pdf".to_owned(),
+            draft: false,
+            stop_on_first_error: false,
+        }
+    }
+}
+
+// Settings of olsync, read from the global config file and overridden by .olsync/config of
+// repository. Missing values take their defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    // Base URL of Overleaf instance, e.g. of a self-hosted one.
+    pub instance_url: String,
+    // Name of session used instead of the default one, to work with several accounts.
+    pub profile: Option<String>,
+    // Whether commands overriding files ask for confirmation. Setting it to false is the same as
+    // always passing --yes.
+    pub confirm: bool,
+    // Path of doc compiled as the main document, instead of the one set in project settings.
+    pub main_document: Option<String>,
+    // Folder of remote project which repository mirrors, instead of the whole project.
+    pub remote_dir: Option<String>,
+    // Patterns of files which are never pushed, in addition to .olsyncignore.
+    pub ignore: Vec<String>,
+    pub backup: BackupSettings,
+    pub compile: CompileSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            instance_url: BASE_URL.to_owned(),
+            profile: None,
+            confirm: true,
+            main_document: None,
+            remote_dir: None,
+            ignore: Vec::new(),
+            backup: BackupSettings::default(),
+            compile: CompileSettings::default(),
+        }
+    }
+}
+
+static CURRENT_SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+// Set settings of the running process, used where no repository is at hand, e.g. to resolve URL
+// of Overleaf instance. They can be set only once.
+pub fn init_settings(settings: Settings) {
+    let _ = CURRENT_SETTINGS.set(settings);
+}
+
+// Settings of the running process, or the defaults if they have not been set.
+pub fn current_settings() -> &'static Settings {
+    CURRENT_SETTINGS.get_or_init(Settings::default)
+}
+
+// Path of the global config file, $XDG_CONFIG_HOME/olsync/config.toml by default.
+pub fn global_config_path() -> Result<PathBuf> {
+    Ok(config_dir()?.join("config.toml"))
+}
+
+// Path of config file of repository in given root directory.
+pub fn repo_config_path(repo_root: &Path) -> PathBuf {
+    repo_root.join(".olsync").join("config")
+}
+
+// Read content of config file. Missing file is the same as an empty one.
+fn read_config_file(path: &Path) -> Result<String> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e).context(format!("Failed to read config file {}.", path.display())),
+    }
+}
+
+// Read config file into a table. Missing file is the same as an empty one.
+fn read_config_table(path: &Path) -> Result<Table> {
+    read_config_file(path)?
+        .parse()
+        .context(format!("Failed to parse config file {}.", path.display()))
+}
+
+// Override values in base table with the ones in overrides, merging sections key by key.
+fn merge_tables(base: &mut Table, overrides: Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base_section)), Value::Table(section)) => {
+                merge_tables(base_section, section)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn check_key(key: &str) -> Result<()> {
+    if !CONFIG_KEYS.contains(&key) {
+        bail!(
+            "Unknown config key {key}. Known keys are: {}.",
+            CONFIG_KEYS.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+impl Settings {
+    // Load settings of repository in given root directory, or only the global ones without it.
+    pub fn load(repo_root: Option<&Path>) -> Result<Self> {
+        let mut table = read_config_table(&global_config_path()?)?;
+
+        if let Some(repo_root) = repo_root {
+            merge_tables(&mut table, read_config_table(&repo_config_path(repo_root))?);
+        }
+
+        Value::Table(table)
+            .try_into()
+            .map_err(|e| anyhow!("Invalid configuration: {e}"))
+    }
+
+    // Value under given key, or None if it is not set and has no default.
+    pub fn get(&self, key: &str) -> Result<Option<Value>> {
+        check_key(key)?;
+
+        let mut value = Value::try_from(self)?;
+
+        for part in key.split('.') {
+            match value.get(part) {
+                Some(inner) => value = inner.clone(),
+                None => return Ok(None),
+            }
+        }
+
+        Ok(Some(value))
+    }
+
+    // Path in remote project of file given by path relative to repository root.
+    pub fn remote_path(&self, path: &str) -> String {
+        match self.remote_dir.as_deref().map(|dir| dir.trim_matches('/')) {
+            Some(dir) if !dir.is_empty() => {
+                format!("{dir}/{path}").trim_end_matches('/').to_owned()
+            }
+            _ => path.to_owned(),
+        }
+    }
+
+    // Path relative to repository root of file given by path in remote project, or None if the
+    // file is outside of remote directory mirrored by repository.
+    pub fn local_path(&self, remote_path: &str) -> Option<String> {
+        match self.remote_dir.as_deref().map(|dir| dir.trim_matches('/')) {
+            Some(dir) if !dir.is_empty() => remote_path
+                .strip_prefix(dir)
+                .and_then(|path| path.strip_prefix('/'))
+                .map(str::to_owned),
+            _ => Some(remote_path.to_owned()),
+        }
+    }
+
+    // Fail unless settings point to Overleaf instance with given base URL, so that a client of
+    // one instance is never used for repository of another one.
+    pub fn check_instance(&self, instance_url: &str) -> Result<()> {
+        if self.instance_url.trim_end_matches('/') != instance_url.trim_end_matches('/') {
+            bail!(
+                "Repository uses Overleaf instance {}, but the session is for {instance_url}.",
+                self.instance_url
+            )
+        }
+
+        Ok(())
+    }
+}
+
+// Set value under given key in config file. Value is parsed as TOML, e.g. true or ["*.log"], and
+// taken as a string otherwise. Other contents of the file, including comments, are preserved.
+pub fn set_config_value(path: &Path, key: &str, raw_value: &str) -> Result<()> {
+    check_key(key)?;
+
+    let mut document: DocumentMut = read_config_file(path)?
+        .parse()
+        .context(format!("Failed to parse config file {}.", path.display()))?;
+
+    let value = raw_value
+        .parse::<toml_edit::Value>()
+        .unwrap_or_else(|_| raw_value.into());
+
+    match key.split_once('.') {
+        Some((section, name)) => document[section][name] = toml_edit::value(value),
+        None => document[key] = toml_edit::value(value),
+    }
+
+    let content = document.to_string();
+
+    let settings = toml::from_str::<Settings>(&content)
+        .map_err(|e| anyhow!("Invalid value {raw_value} of {key}: {}", e.message()))?;
+
+    if let Some(max_age) = &settings.backup.max_age {
+        parse_age(max_age)?;
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, content).context(format!("Failed to save config file {}.", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn repository_config_overrides_global_one() {
+        let mut table: Table = "confirm = false\n[compile]\ndraft = true\noutput = \"a.pdf\"\n"
+            .parse()
+            .unwrap();
+
+        merge_tables(
+            &mut table,
+            "[compile]\noutput = \"b.pdf\"\n".parse().unwrap(),
+        );
+
+        let settings: Settings = Value::Table(table).try_into().unwrap();
+
+        assert!(!settings.confirm);
+        assert!(settings.compile.draft);
+        assert_eq!(settings.compile.output, "b.pdf");
+        assert!(settings.backup.enabled);
+    }
+
+    #[test]
+    fn set_validates_and_preserves_comments() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config");
+        fs::write(&path, "# my settings\nconfirm = true\n").unwrap();
+
+        set_config_value(&path, "backup.enabled", "false").unwrap();
+        set_config_value(&path, "main_document", "thesis.tex").unwrap();
+
+        assert!(set_config_value(&path, "confirm", "maybe").is_err());
+        assert!(set_config_value(&path, "unknown", "1").is_err());
+
+        let content = fs::read_to_string(&path).unwrap();
+        let settings: Settings = toml::from_str(&content).unwrap();
+
+        assert!(content.starts_with("# my settings"));
+        assert!(!settings.backup.enabled);
+        assert_eq!(settings.main_document.as_deref(), Some("thesis.tex"));
+    }
+
+    #[test]
+    fn unreadable_config_is_not_overwritten() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config");
+        fs::write(&path, b"confirm = false\nprofile = \"work\"\n\xff\n").unwrap();
+
+        assert!(read_config_table(&path).is_err());
+        assert!(set_config_value(&path, "compile.draft", "true").is_err());
+        assert!(fs::read(&path).unwrap().ends_with(b"\xff\n"));
+        assert!(read_config_table(&dir.path().join("missing"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn paths_are_mapped_into_remote_dir() {
+        let settings = Settings {
+            remote_dir: Some("paper/".to_owned()),
+            ..Settings::default()
+        };
+
+        assert_eq!(settings.remote_path("main.tex"), "paper/main.tex");
+        assert_eq!(settings.remote_path(""), "paper");
+        assert_eq!(
+            settings.local_path("paper/figures/a.png").as_deref(),
+            Some("figures/a.png")
+        );
+        assert_eq!(settings.local_path("notes.tex"), None);
+        assert_eq!(settings.local_path("paperback/a.tex"), None);
+    }
+}
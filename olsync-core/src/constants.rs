@@ -0,0 +1,40 @@
+pub const SESSION_COOKIE_NAME: &str = "overleaf_session2";
+pub const GCLB_COOKIE_NAME: &str = "GCLB";
+
+pub const BASE_URL: &str = "https://www.overleaf.com";
+// Environment variable overriding BASE_URL, e.g. to use a local mock server in tests.
+pub const BASE_URL_ENV: &str = "OLSYNC_BASE_URL";
+// Environment variable with directory used for all global files of olsync instead of the
+// standard ones, e.g. to keep CI runs away from files of the user.
+pub const OLSYNC_HOME_ENV: &str = "OLSYNC_HOME";
+pub const LOGIN_URL: &str = "https://www.overleaf.com/login";
+pub const PROJECTS_URL: &str = "https://www.overleaf.com/project";
+pub const DOWNLOAD_PROJECT_URL: &str = "https://www.overleaf.com/project/{}/download/zip";
+pub const DOWNLOAD_DOC_URL: &str = "https://www.overleaf.com/project/{}/doc/{}/download";
+pub const DOWNLOAD_FILE_URL: &str = "https://www.overleaf.com/project/{}/file/{}";
+pub const CREATE_FOLDER_URL: &str = "https://www.overleaf.com/project/{}/folder";
+pub const DELETE_ENTITY_URL: &str = "https://www.overleaf.com/project/{}/{}/{}";
+pub const UPLOAD_FILE_URL: &str = "https://www.overleaf.com/project/{}/upload";
+pub const PROJECT_SETTINGS_URL: &str = "https://www.overleaf.com/project/{}/settings";
+pub const PROJECT_UPDATES_URL: &str = "https://www.overleaf.com/project/{}/updates";
+pub const PROJECT_LABELS_URL: &str = "https://www.overleaf.com/project/{}/labels";
+pub const PROJECT_LABEL_URL: &str = "https://www.overleaf.com/project/{}/labels/{}";
+pub const PROJECT_VERSION_ZIP_URL: &str = "https://www.overleaf.com/project/{}/version/{}/zip";
+pub const RESTORE_FILE_URL: &str = "https://www.overleaf.com/project/{}/restore_file";
+pub const PROJECT_MEMBERS_URL: &str = "https://www.overleaf.com/project/{}/members";
+pub const PROJECT_MEMBER_URL: &str = "https://www.overleaf.com/project/{}/users/{}";
+pub const PROJECT_INVITES_URL: &str = "https://www.overleaf.com/project/{}/invites";
+pub const PROJECT_INVITE_URL: &str = "https://www.overleaf.com/project/{}/invite";
+pub const TRANSFER_OWNERSHIP_URL: &str = "https://www.overleaf.com/project/{}/transfer-ownership";
+pub const PROJECT_ADMIN_SETTINGS_URL: &str = "https://www.overleaf.com/project/{}/settings/admin";
+pub const PROJECT_TOKENS_URL: &str = "https://www.overleaf.com/project/{}/tokens";
+pub const COMPILE_PROJECT_URL: &str = "https://www.overleaf.com/project/{}/compile";
+pub const TAG_URL: &str = "https://www.overleaf.com/tag";
+pub const SOCKET_URL: &str = "https://www.overleaf.com/socket.io/socket.io.js";
+
+pub const ONE_HOUR_IN_SECONDS: u64 = 3600;
+
+pub const HISTORY_POLL_ATTEMPTS: u32 = 10;
+pub const HISTORY_POLL_INTERVAL_IN_MILLISECONDS: u64 = 1000;
+
+pub const DAEMON_SOCKET_FILE_NAME: &str = "olsyncd.sock";
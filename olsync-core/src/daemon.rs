@@ -0,0 +1,849 @@
+use crate::{
+    api::OverleafApi,
+    config::Settings,
+    constants::DAEMON_SOCKET_FILE_NAME,
+    follow::follow_project_changes,
+    overleaf_client::{OverleafClient, Project, ProjectDetails},
+    paths::runtime_dir,
+    progress::{SyncAction, SyncEvent},
+    repository::{
+        backup_project, check_project_zip, compile_options, extract_project, find_repo_root,
+        pull_file, push_file, read_project_info, save_last_compile, wipe_project, PushOutcome,
+    },
+    utils::path_to_str,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use log::{error, info};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Component, Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+    sync::{
+        broadcast::{self, error::RecvError},
+        mpsc, Mutex,
+    },
+    task::{self, JoinHandle},
+};
+
+// Error codes defined by JSON-RPC 2.0.
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+
+// How many events are buffered for slow subscribers before they start missing them.
+const EVENT_BUFFER_SIZE: usize = 256;
+
+#[derive(Deserialize)]
+struct Request {
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl From<anyhow::Error> for RpcError {
+    fn from(err: anyhow::Error) -> Self {
+        RpcError {
+            code: SERVER_ERROR,
+            message: err.to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct StatusParams {
+    path: Option<PathBuf>,
+}
+
+#[derive(Deserialize)]
+struct RepositoryParams {
+    path: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct FilesParams {
+    path: PathBuf,
+    #[serde(default)]
+    files: Vec<String>,
+}
+
+// Event of a repository, sent to subscribed clients as `event` notification.
+#[derive(Debug, Clone, Serialize)]
+struct DaemonEvent {
+    repository: String,
+    #[serde(flatten)]
+    event: SyncEvent,
+}
+
+// Repository served by the daemon, with cached project details and followed remote project.
+struct Repository {
+    root: PathBuf,
+    project: Project,
+    details: Option<Arc<ProjectDetails>>,
+    // Set when remote file tree changes, so that cached details are fetched again.
+    details_stale: Arc<AtomicBool>,
+    follow: Option<JoinHandle<()>>,
+}
+
+impl Repository {
+    fn is_following(&self) -> bool {
+        self.follow
+            .as_ref()
+            .is_some_and(|handle| !handle.is_finished())
+    }
+
+    fn status(&self) -> Value {
+        json!({
+            "path": path_to_str(&self.root),
+            "projectId": self.project.id,
+            "projectName": self.project.name,
+            "following": self.is_following(),
+        })
+    }
+
+    // Turn path given by client, absolute or relative to repository root, into relative one.
+    fn relative_path(&self, path: &str) -> Result<String> {
+        let path = Path::new(path);
+
+        let relative = if path.is_absolute() {
+            path.strip_prefix(&self.root)
+                .map_err(|_| anyhow!("File {} is not in the repository.", path_to_str(path)))?
+        } else {
+            path
+        };
+
+        if relative
+            .components()
+            .any(|component| matches!(component, Component::ParentDir))
+        {
+            bail!("File {} is not in the repository.", path_to_str(path))
+        }
+
+        Ok(path_to_str(relative).to_owned())
+    }
+}
+
+struct Daemon<A: OverleafApi> {
+    overleaf_client: Arc<A>,
+    repositories: Mutex<HashMap<PathBuf, Arc<Mutex<Repository>>>>,
+    events: broadcast::Sender<DaemonEvent>,
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    // Methods without required params may be called without any.
+    let params = if params.is_null() { json!({}) } else { params };
+
+    serde_json::from_value(params).map_err(|e| RpcError {
+        code: INVALID_PARAMS,
+        message: format!("Invalid params: {e}."),
+    })
+}
+
+impl<A: OverleafApi + 'static> Daemon<A> {
+    fn new(overleaf_client: A) -> Self {
+        Daemon {
+            overleaf_client: Arc::new(overleaf_client),
+            repositories: Mutex::new(HashMap::new()),
+            events: broadcast::channel(EVENT_BUFFER_SIZE).0,
+        }
+    }
+
+    fn emit(&self, repository: &Repository, event: SyncEvent) {
+        let _ = self.events.send(DaemonEvent {
+            repository: path_to_str(&repository.root).to_owned(),
+            event,
+        });
+    }
+
+    // Get repository containing given path, loading it on first use.
+    async fn repository(&self, path: &Path) -> Result<Arc<Mutex<Repository>>> {
+        let root = find_repo_root(path).context(format!(
+            "{} is not in an olsync repository.",
+            path_to_str(path)
+        ))?;
+
+        let mut repositories = self.repositories.lock().await;
+
+        if let Some(repository) = repositories.get(&root) {
+            return Ok(repository.clone());
+        }
+
+        Settings::load(Some(&root))?.check_instance(self.overleaf_client.instance_url())?;
+
+        info!("Serving repository {}.", path_to_str(&root));
+
+        let repository = Arc::new(Mutex::new(Repository {
+            project: read_project_info(&root)?,
+            root: root.clone(),
+            details: None,
+            details_stale: Arc::new(AtomicBool::new(false)),
+            follow: None,
+        }));
+
+        repositories.insert(root, repository.clone());
+
+        Ok(repository)
+    }
+
+    // Cached project details of repository. They are fetched by a blocking subprocess, so it runs
+    // outside of the async runtime and without holding the lock of the repository.
+    async fn details(&self, repository: &Mutex<Repository>) -> Result<Arc<ProjectDetails>> {
+        let project_id = {
+            let mut repository = repository.lock().await;
+
+            if repository.details_stale.swap(false, Ordering::SeqCst) {
+                repository.details = None;
+            }
+
+            if let Some(details) = &repository.details {
+                return Ok(details.clone());
+            }
+
+            repository.project.id.clone()
+        };
+
+        let overleaf_client = self.overleaf_client.clone();
+        let details = Arc::new(
+            task::spawn_blocking(move || overleaf_client.get_project_details(&project_id))
+                .await??,
+        );
+
+        repository.lock().await.details = Some(details.clone());
+
+        Ok(details)
+    }
+
+    async fn status(&self, params: StatusParams) -> Result<Value> {
+        if let Some(path) = params.path {
+            let repository = self.repository(&path).await?;
+            let status = repository.lock().await.status();
+            return Ok(status);
+        }
+
+        let repositories: Vec<_> = self.repositories.lock().await.values().cloned().collect();
+        let mut statuses = Vec::new();
+
+        for repository in repositories {
+            statuses.push(repository.lock().await.status());
+        }
+
+        Ok(json!({ "repositories": statuses }))
+    }
+
+    async fn push(&self, params: FilesParams) -> Result<Value> {
+        if params.files.is_empty() {
+            bail!("No files to push.")
+        }
+
+        let repository = self.repository(&params.path).await?;
+        let details = self.details(&repository).await?;
+        let mut repository = repository.lock().await;
+
+        let root_folder = details
+            .root_folder
+            .first()
+            .context("Project details do not contain root folder.")?;
+
+        let root = repository.root.clone();
+        let project_id = repository.project.id.clone();
+        let settings = Settings::load(Some(&root))?;
+
+        let mut created_folders = HashMap::new();
+        let mut events = Vec::new();
+        let mut uploaded = false;
+
+        for file in &params.files {
+            let path = repository.relative_path(file)?;
+
+            let result = push_file(
+                self.overleaf_client.as_ref(),
+                &project_id,
+                root_folder,
+                &root,
+                &settings,
+                &path,
+                &mut created_folders,
+            )
+            .await;
+
+            uploaded |= matches!(result, Ok(PushOutcome::UploadedFile));
+
+            let event = SyncEvent::from_push(path, &result);
+            self.emit(&repository, event.clone());
+            events.push(event);
+        }
+
+        if uploaded {
+            repository.details = None;
+        }
+
+        Ok(json!({ "events": events }))
+    }
+
+    async fn pull(&self, params: FilesParams) -> Result<Value> {
+        let repository = self.repository(&params.path).await?;
+
+        // Details are needed only to find pulled files.
+        let details = match params.files.is_empty() {
+            true => None,
+            false => Some(self.details(&repository).await?),
+        };
+
+        let mut repository = repository.lock().await;
+
+        let root = repository.root.clone();
+        let project_id = repository.project.id.clone();
+
+        let settings = Settings::load(Some(&root))?;
+
+        // Without files, the whole project is pulled, after backing up local state unless
+        // backups are disabled in settings.
+        if params.files.is_empty() {
+            let archive = self
+                .overleaf_client
+                .download_project_zip(project_id)
+                .await?;
+
+            check_project_zip(&archive)?;
+
+            let backup = match settings.backup.enabled {
+                true => Some(backup_project(&root)?),
+                false => None,
+            };
+
+            wipe_project(&root)?;
+            extract_project(archive.to_vec(), &root)?;
+
+            repository.details = None;
+
+            return Ok(json!({ "backup": backup.as_deref().map(path_to_str) }));
+        }
+
+        let paths = params
+            .files
+            .iter()
+            .map(|file| repository.relative_path(file))
+            .collect::<Result<Vec<_>>>()?;
+
+        let root_folder = details
+            .as_ref()
+            .and_then(|details| details.root_folder.first())
+            .context("Project details do not contain root folder.")?;
+
+        let mut entities = Vec::new();
+
+        for path in paths {
+            for (remote_path, entity_type, entity) in root_folder
+                .find_entities(&settings.remote_path(&path))
+                .context(format!("File {path} does not exist in remote project."))?
+            {
+                if let Some(path) = settings.local_path(&remote_path) {
+                    entities.push((path, entity_type, entity.id.clone()));
+                }
+            }
+        }
+
+        let mut events = Vec::new();
+
+        for (path, entity_type, entity_id) in entities {
+            let event = match pull_file(
+                self.overleaf_client.as_ref(),
+                &project_id,
+                &root,
+                &path,
+                entity_type,
+                &entity_id,
+            )
+            .await
+            {
+                Ok(()) => SyncEvent::new(SyncAction::Pulled, path),
+                Err(err) => SyncEvent::with_detail(SyncAction::Failed, path, err.to_string()),
+            };
+
+            self.emit(&repository, event.clone());
+            events.push(event);
+        }
+
+        Ok(json!({ "events": events }))
+    }
+
+    async fn compile(&self, params: RepositoryParams) -> Result<Value> {
+        let repository = self.repository(&params.path).await?;
+        let repository = repository.lock().await;
+
+        let settings = Settings::load(Some(&repository.root))?;
+
+        // Main document is looked up in project details, fetched by a blocking subprocess.
+        let options = {
+            let overleaf_client = self.overleaf_client.clone();
+            let project_id = repository.project.id.clone();
+            let settings = settings.clone();
+
+            task::spawn_blocking(move || {
+                compile_options(overleaf_client.as_ref(), &project_id, &settings)
+            })
+            .await??
+        };
+
+        let compile_result = self
+            .overleaf_client
+            .request_compile(&repository.project.id, &options)
+            .await?;
+
+        save_last_compile(&repository.root, &compile_result)?;
+
+        let pdf_path = match compile_result.output_file("output.pdf") {
+            Some(output_file) => {
+                let pdf = self
+                    .overleaf_client
+                    .download_output_file(&compile_result, output_file)
+                    .await?;
+
+                let pdf_path = repository.root.join(&settings.compile.output);
+                fs::write(&pdf_path, pdf).context("Failed to save the PDF.")?;
+
+                Some(path_to_str(&pdf_path).to_owned())
+            }
+            None => None,
+        };
+
+        Ok(json!({
+            "status": compile_result.status,
+            "success": compile_result.is_success(),
+            "pdf": pdf_path,
+        }))
+    }
+
+    // Follow remote project of repository, unless it is already followed.
+    async fn start_following(&self, path: &Path) -> Result<PathBuf> {
+        let repository = self.repository(path).await?;
+        let mut repository = repository.lock().await;
+
+        if repository.is_following() {
+            return Ok(repository.root.clone());
+        }
+
+        let overleaf_client = self.overleaf_client.clone();
+        let events = self.events.clone();
+        let root = repository.root.clone();
+        let project_id = repository.project.id.clone();
+        let details_stale = repository.details_stale.clone();
+
+        repository.follow = Some(tokio::spawn(async move {
+            let on_event = |event: SyncEvent| {
+                if matches!(event.action, SyncAction::Moved | SyncAction::Removed) {
+                    details_stale.store(true, Ordering::SeqCst);
+                }
+
+                let _ = events.send(DaemonEvent {
+                    repository: path_to_str(&root).to_owned(),
+                    event,
+                });
+            };
+
+            if let Err(err) =
+                follow_project_changes(overleaf_client.as_ref(), &project_id, &root, &on_event)
+                    .await
+            {
+                error!("Stopped following {}: {err}", path_to_str(&root));
+                on_event(SyncEvent::with_detail(
+                    SyncAction::Failed,
+                    String::new(),
+                    err.to_string(),
+                ));
+            }
+        }));
+
+        Ok(repository.root.clone())
+    }
+
+    // Follow repository and forward its events to client as notifications.
+    async fn subscribe(
+        &self,
+        params: RepositoryParams,
+        client: &mpsc::UnboundedSender<String>,
+    ) -> Result<(Value, JoinHandle<()>)> {
+        let root = path_to_str(&self.start_following(&params.path).await?).to_owned();
+
+        let mut receiver = self.events.subscribe();
+        let client = client.clone();
+        let repository = root.clone();
+
+        let forwarder = tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) if event.repository == repository => {
+                        let notification = json!({
+                            "jsonrpc": "2.0",
+                            "method": "event",
+                            "params": event,
+                        });
+
+                        if client.send(notification.to_string()).is_err() {
+                            break;
+                        }
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        Ok((json!({ "subscribed": root }), forwarder))
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "status" => Ok(self.status(parse_params(params)?).await?),
+            "push" => Ok(self.push(parse_params(params)?).await?),
+            "pull" => Ok(self.pull(parse_params(params)?).await?),
+            "compile" => Ok(self.compile(parse_params(params)?).await?),
+            _ => Err(RpcError {
+                code: METHOD_NOT_FOUND,
+                message: format!("Method {method} not found."),
+            }),
+        }
+    }
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+// Serve JSON-RPC requests of a single client, one per line, until it disconnects.
+async fn handle_connection<A: OverleafApi + 'static>(daemon: Arc<Daemon<A>>, stream: UnixStream) {
+    let (reader, mut writer) = stream.into_split();
+    let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+
+    let writer_task = tokio::spawn(async move {
+        while let Some(line) = rx.recv().await {
+            if writer
+                .write_all(format!("{line}\n").as_bytes())
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    let mut lines = BufReader::new(reader).lines();
+    let mut subscriptions = Vec::new();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let request: Request = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                let error = RpcError {
+                    code: PARSE_ERROR,
+                    message: format!("Invalid request: {e}."),
+                };
+                let _ = tx.send(error_response(Value::Null, error).to_string());
+                continue;
+            }
+        };
+
+        let result = if request.method == "subscribe" {
+            match parse_params(request.params) {
+                Ok(params) => daemon
+                    .subscribe(params, &tx)
+                    .await
+                    .map(|(result, forwarder)| {
+                        subscriptions.push(forwarder);
+                        result
+                    })
+                    .map_err(RpcError::from),
+                Err(err) => Err(err),
+            }
+        } else {
+            daemon.call(&request.method, request.params).await
+        };
+
+        // Requests without id are notifications, which get no response.
+        let Some(id) = request.id else {
+            continue;
+        };
+
+        let response = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => error_response(id, error),
+        };
+
+        let _ = tx.send(response.to_string());
+    }
+
+    for subscription in subscriptions {
+        subscription.abort();
+    }
+
+    drop(tx);
+    let _ = writer_task.await;
+}
+
+// Default location of daemon socket, in the runtime directory if there is one.
+pub fn default_socket_path() -> Result<PathBuf> {
+    Ok(runtime_dir()?.join(DAEMON_SOCKET_FILE_NAME))
+}
+
+// Run daemon serving JSON-RPC requests on Unix socket until interrupted.
+pub async fn run_daemon(overleaf_client: OverleafClient, socket_path: &Path) -> Result<()> {
+    if socket_path.exists() {
+        if UnixStream::connect(socket_path).await.is_ok() {
+            bail!("Daemon is already running on {}.", path_to_str(socket_path))
+        }
+
+        // Socket left behind by a daemon which did not shut down cleanly.
+        fs::remove_file(socket_path)?;
+    }
+
+    let listener = UnixListener::bind(socket_path)
+        .context(format!("Failed to listen on {}.", path_to_str(socket_path)))?;
+
+    let daemon = Arc::new(Daemon::new(overleaf_client));
+
+    info!(
+        "Listening on {}. Press Ctrl+C to stop.",
+        path_to_str(socket_path)
+    );
+
+    let result = loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle_connection(daemon.clone(), stream));
+                }
+                Err(err) => break Err(anyhow!("Failed to accept connection: {err}")),
+            },
+            _ = tokio::signal::ctrl_c() => break Ok(()),
+        }
+    };
+
+    let _ = fs::remove_file(socket_path);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fake::FakeOverleaf;
+    use tempfile::TempDir;
+    use tokio::{
+        io::Lines,
+        net::unix::{OwnedReadHalf, OwnedWriteHalf},
+    };
+
+    struct Client {
+        lines: Lines<BufReader<OwnedReadHalf>>,
+        writer: OwnedWriteHalf,
+    }
+
+    impl Client {
+        async fn connect(socket_path: &Path) -> Self {
+            let (reader, writer) = UnixStream::connect(socket_path).await.unwrap().into_split();
+
+            Client {
+                lines: BufReader::new(reader).lines(),
+                writer,
+            }
+        }
+
+        async fn send(&mut self, line: &str) -> Value {
+            self.writer
+                .write_all(format!("{line}\n").as_bytes())
+                .await
+                .unwrap();
+
+            let response = self.lines.next_line().await.unwrap().unwrap();
+            serde_json::from_str(&response).unwrap()
+        }
+
+        async fn call(&mut self, method: &str, params: Value) -> Value {
+            let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+            self.send(&request.to_string()).await
+        }
+    }
+
+    // Daemon serving fake Overleaf with a single project, cloned into repository in temporary
+    // directory, with socket in the same directory.
+    struct TestDaemon {
+        dir: TempDir,
+        daemon: Arc<Daemon<FakeOverleaf>>,
+        project: Project,
+        doc_id: String,
+    }
+
+    impl TestDaemon {
+        fn start() -> Self {
+            let overleaf = FakeOverleaf::new();
+            let project = overleaf.add_project("thesis");
+            let doc_id = overleaf.add_doc(&project.id, "main.tex", "remote").unwrap();
+
+            let dir = tempfile::tempdir().unwrap();
+            fs::create_dir_all(dir.path().join("thesis").join(".olsync")).unwrap();
+            fs::write(
+                dir.path()
+                    .join("thesis")
+                    .join(".olsync")
+                    .join("projectinfo"),
+                serde_json::to_string(&project).unwrap(),
+            )
+            .unwrap();
+
+            let daemon = Arc::new(Daemon::new(overleaf));
+            let listener = UnixListener::bind(dir.path().join("olsyncd.sock")).unwrap();
+            let serving = daemon.clone();
+
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(handle_connection(serving.clone(), stream));
+                }
+            });
+
+            TestDaemon {
+                dir,
+                daemon,
+                project,
+                doc_id,
+            }
+        }
+
+        fn repo_root(&self) -> PathBuf {
+            self.dir.path().join("thesis")
+        }
+
+        async fn client(&self) -> Client {
+            Client::connect(&self.dir.path().join("olsyncd.sock")).await
+        }
+    }
+
+    #[tokio::test]
+    async fn repositories_are_served_over_socket() {
+        let test_daemon = TestDaemon::start();
+        let repo_root = test_daemon.repo_root();
+        let overleaf = test_daemon.daemon.overleaf_client.as_ref();
+        let mut client = test_daemon.client().await;
+
+        let response = client.call("status", json!({ "path": repo_root })).await;
+        assert_eq!(response["result"]["projectName"], "thesis");
+        assert_eq!(response["result"]["following"], false);
+
+        fs::write(repo_root.join("main.tex"), "local").unwrap();
+
+        let response = client
+            .call("push", json!({ "path": repo_root, "files": ["main.tex"] }))
+            .await;
+        assert_eq!(
+            response["result"]["events"],
+            json!([{ "action": "updated", "path": "main.tex" }])
+        );
+        assert_eq!(
+            overleaf.content(&test_daemon.project.id, "main.tex"),
+            Some(b"local".to_vec())
+        );
+
+        overleaf
+            .update_doc(&test_daemon.project.id, &test_daemon.doc_id, "remote edit")
+            .await
+            .unwrap();
+
+        let response = client
+            .call("pull", json!({ "path": repo_root, "files": ["main.tex"] }))
+            .await;
+        assert_eq!(
+            response["result"]["events"],
+            json!([{ "action": "pulled", "path": "main.tex" }])
+        );
+        assert_eq!(
+            fs::read_to_string(repo_root.join("main.tex")).unwrap(),
+            "remote edit"
+        );
+
+        let response = client.call("status", Value::Null).await;
+        assert_eq!(
+            response["result"]["repositories"].as_array().unwrap().len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_requests_get_error_responses() {
+        let test_daemon = TestDaemon::start();
+        let mut client = test_daemon.client().await;
+
+        let response = client.call("rename", json!({})).await;
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = client.send("{\"method\": ").await;
+        assert_eq!(response["id"], Value::Null);
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+
+        let response = client
+            .call(
+                "push",
+                json!({ "path": test_daemon.repo_root(), "files": "main.tex" }),
+            )
+            .await;
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+        let response = client.call("pull", json!({})).await;
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+        let response = client
+            .call(
+                "push",
+                json!({ "path": test_daemon.dir.path(), "files": ["a.tex"] }),
+            )
+            .await;
+        assert_eq!(response["error"]["code"], SERVER_ERROR);
+
+        // Repository of another Overleaf instance than the one of the session.
+        let other_root = test_daemon.dir.path().join("other");
+        fs::create_dir_all(other_root.join(".olsync")).unwrap();
+        fs::write(
+            other_root.join(".olsync").join("projectinfo"),
+            serde_json::to_string(&test_daemon.project).unwrap(),
+        )
+        .unwrap();
+        fs::write(
+            other_root.join(".olsync").join("config"),
+            "instance_url = \"https://overleaf.example.com\"\n",
+        )
+        .unwrap();
+
+        let response = client
+            .call("push", json!({ "path": other_root, "files": ["main.tex"] }))
+            .await;
+        assert_eq!(response["error"]["code"], SERVER_ERROR);
+        assert!(response["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("https://overleaf.example.com"));
+    }
+}
@@ -0,0 +1,127 @@
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::io;
+use thiserror::Error;
+
+// Errors which callers may want to tell apart. They are still passed around as anyhow::Error,
+// possibly wrapped in context, and their category can be recovered with ErrorKind::of.
+#[derive(Debug, Error)]
+pub enum OlsyncError {
+    #[error("Session has expired or is invalid. Use olsync login to log in again.")]
+    AuthExpired,
+
+    #[error("Project {0} not found.")]
+    ProjectNotFound(String),
+
+    #[error(
+        "There are {} projects named {name} (ids: {}). Use project id instead.",
+        ids.len(),
+        ids.join(", ")
+    )]
+    AmbiguousProjectName { name: String, ids: Vec<String> },
+
+    #[error("{0}")]
+    Conflict(String),
+
+    #[error("{action} with response:\n{status}: {body}.")]
+    RemoteRejected {
+        action: String,
+        status: StatusCode,
+        body: String,
+    },
+}
+
+// Category of error, each with its own exit code of olsync process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    Other,
+    AuthExpired,
+    ProjectNotFound,
+    AmbiguousProjectName,
+    Conflict,
+    Network,
+    RemoteRejected,
+    Filesystem,
+}
+
+impl OlsyncError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            OlsyncError::AuthExpired => ErrorKind::AuthExpired,
+            OlsyncError::ProjectNotFound(_) => ErrorKind::ProjectNotFound,
+            OlsyncError::AmbiguousProjectName { .. } => ErrorKind::AmbiguousProjectName,
+            OlsyncError::Conflict(_) => ErrorKind::Conflict,
+            OlsyncError::RemoteRejected { .. } => ErrorKind::RemoteRejected,
+        }
+    }
+}
+
+impl ErrorKind {
+    // Category of the first error in chain which has one. Errors of reqwest are network errors
+    // and I/O errors are filesystem errors.
+    pub fn of(error: &anyhow::Error) -> ErrorKind {
+        error
+            .chain()
+            .find_map(|cause| {
+                if let Some(olsync_error) = cause.downcast_ref::<OlsyncError>() {
+                    Some(olsync_error.kind())
+                } else if cause.is::<reqwest::Error>() {
+                    Some(ErrorKind::Network)
+                } else if cause.is::<io::Error>() {
+                    Some(ErrorKind::Filesystem)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(ErrorKind::Other)
+    }
+
+    // Exit code of olsync process failing with error of this kind. Code 2 is left out, since it
+    // is used by clap for invalid arguments.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorKind::Other => 1,
+            ErrorKind::AuthExpired => 3,
+            ErrorKind::ProjectNotFound => 4,
+            ErrorKind::AmbiguousProjectName => 5,
+            ErrorKind::Conflict => 6,
+            ErrorKind::Network => 7,
+            ErrorKind::RemoteRejected => 8,
+            ErrorKind::Filesystem => 9,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Context};
+
+    #[test]
+    fn kind_is_found_under_context() {
+        let error = anyhow!(OlsyncError::ProjectNotFound("thesis".to_owned()))
+            .context("Failed to clone project.");
+
+        assert_eq!(ErrorKind::of(&error), ErrorKind::ProjectNotFound);
+        assert_eq!(ErrorKind::of(&error).exit_code(), 4);
+    }
+
+    #[test]
+    fn io_errors_are_filesystem_errors() {
+        let error = std::fs::read("/nonexistent/olsync")
+            .context("Failed to read file.")
+            .unwrap_err();
+
+        assert_eq!(ErrorKind::of(&error), ErrorKind::Filesystem);
+    }
+
+    #[test]
+    fn plain_errors_have_no_kind() {
+        assert_eq!(
+            ErrorKind::of(&anyhow!("Something failed.")),
+            ErrorKind::Other
+        );
+        assert_eq!(ErrorKind::Other.exit_code(), 1);
+    }
+}
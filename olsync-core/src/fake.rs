@@ -0,0 +1,499 @@
+use anyhow::{anyhow, bail, Context, Result};
+use bytes::Bytes;
+use std::{
+    collections::HashMap,
+    io::{Cursor, Write},
+    mem,
+    sync::{Mutex, MutexGuard},
+};
+use zip::{write::FileOptions, ZipWriter};
+
+use crate::{
+    api::OverleafApi,
+    constants::BASE_URL,
+    error::OlsyncError,
+    follow::find_folder_by_id_mut,
+    overleaf_client::{
+        CompileOptions, CompileResult, Entity, EntityType, Folder, OutputFile, Project,
+        ProjectDetails, ProjectEvent, ProjectEvents, ProjectsList,
+    },
+};
+
+struct FakeProject {
+    project: Project,
+    root_folder: Folder,
+    // Content of docs and files by their ids.
+    contents: HashMap<String, Vec<u8>>,
+    // Changes of collaborators, delivered to the next follower of the project.
+    events: Vec<ProjectEvent>,
+}
+
+#[derive(Default)]
+struct FakeState {
+    projects: Vec<FakeProject>,
+    next_id: u64,
+    // Whether project downloads return an HTML page instead of zip, like a broken proxy would.
+    invalid_zips: bool,
+}
+
+impl FakeState {
+    fn new_id(&mut self) -> String {
+        self.next_id += 1;
+        format!("{:024x}", self.next_id)
+    }
+
+    fn project(&mut self, project_id: &str) -> Result<&mut FakeProject> {
+        self.projects
+            .iter_mut()
+            .find(|fake_project| fake_project.project.id == project_id)
+            .ok_or(anyhow!(OlsyncError::ProjectNotFound(project_id.to_owned())))
+    }
+}
+
+// Remove entity of given type and id from the tree. Returns ids of removed docs and files.
+fn remove_entity(folder: &mut Folder, entity_type: EntityType, id: &str) -> Option<Vec<String>> {
+    let removed = match entity_type {
+        EntityType::Doc => folder
+            .docs
+            .iter()
+            .position(|doc| doc.id == id)
+            .map(|idx| vec![folder.docs.remove(idx).id]),
+        EntityType::File => folder
+            .file_refs
+            .iter()
+            .position(|file| file.id == id)
+            .map(|idx| vec![folder.file_refs.remove(idx).id]),
+        EntityType::Folder => folder
+            .folders
+            .iter()
+            .position(|subfolder| subfolder.id == id)
+            .map(|idx| {
+                folder
+                    .folders
+                    .remove(idx)
+                    .all_entities("")
+                    .into_iter()
+                    .map(|(_, _, entity)| entity.id.clone())
+                    .collect()
+            }),
+    };
+
+    removed.or_else(|| {
+        folder
+            .folders
+            .iter_mut()
+            .find_map(|subfolder| remove_entity(subfolder, entity_type, id))
+    })
+}
+
+fn empty_folder(id: String, name: String) -> Folder {
+    Folder {
+        id,
+        name,
+        docs: Vec::new(),
+        file_refs: Vec::new(),
+        folders: Vec::new(),
+    }
+}
+
+// Content of output.pdf produced by every compilation of fake projects.
+pub const FAKE_PDF: &[u8] = b"%PDF-1.5 fake";
+
+// In-memory store of projects implementing OverleafApi, for testing synchronization without
+// live Overleaf. Like Overleaf, it stores uploaded text files as docs and replaces entities with
+// the same name on upload. Compilation always succeeds and produces FAKE_PDF.
+#[derive(Default)]
+pub struct FakeOverleaf {
+    state: Mutex<FakeState>,
+}
+
+impl FakeOverleaf {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, FakeState> {
+        self.state.lock().unwrap()
+    }
+
+    // Make project downloads return an HTML page instead of zip archive.
+    pub fn serve_invalid_zips(&self) {
+        self.state().invalid_zips = true;
+    }
+
+    // Create empty project with given name.
+    pub fn add_project(&self, name: &str) -> Project {
+        let mut state = self.state();
+
+        let project = Project {
+            id: state.new_id(),
+            name: name.to_owned(),
+            last_updated: None,
+        };
+
+        let root_folder = empty_folder(state.new_id(), "rootFolder".to_owned());
+
+        state.projects.push(FakeProject {
+            project: project.clone(),
+            root_folder,
+            contents: HashMap::new(),
+            events: Vec::new(),
+        });
+
+        project
+    }
+
+    // Add doc at given path of the project, creating missing folders. Returns id of the doc.
+    pub fn add_doc(&self, project_id: &str, path: &str, content: &str) -> Result<String> {
+        self.add_entity(project_id, path, content.as_bytes().to_vec(), true)
+    }
+
+    // Add binary file at given path of the project, creating missing folders. Returns id of the
+    // file.
+    pub fn add_file(&self, project_id: &str, path: &str, content: &[u8]) -> Result<String> {
+        self.add_entity(project_id, path, content.to_vec(), false)
+    }
+
+    fn add_entity(
+        &self,
+        project_id: &str,
+        path: &str,
+        content: Vec<u8>,
+        doc: bool,
+    ) -> Result<String> {
+        let mut state = self.state();
+        let id = state.new_id();
+        let folder_ids: Vec<String> = path.split('/').map(|_| state.new_id()).collect();
+
+        let fake_project = state.project(project_id)?;
+        let (folder_path, name) = path.rsplit_once('/').unwrap_or(("", path));
+
+        let mut folder = &mut fake_project.root_folder;
+
+        for (folder_name, folder_id) in folder_path
+            .split('/')
+            .filter(|name| !name.is_empty())
+            .zip(folder_ids)
+        {
+            let idx = match folder.folders.iter().position(|f| f.name == folder_name) {
+                Some(idx) => idx,
+                None => {
+                    folder
+                        .folders
+                        .push(empty_folder(folder_id, folder_name.to_owned()));
+                    folder.folders.len() - 1
+                }
+            };
+
+            folder = &mut folder.folders[idx];
+        }
+
+        let entity = Entity {
+            id: id.clone(),
+            name: name.to_owned(),
+        };
+
+        if doc {
+            folder.docs.push(entity);
+        } else {
+            folder.file_refs.push(entity);
+        }
+
+        fake_project.contents.insert(id.clone(), content);
+
+        Ok(id)
+    }
+
+    // Queue change of a collaborator, which is delivered to the next follower of the project
+    // after the project and its docs are joined.
+    pub fn queue_event(&self, project_id: &str, event: ProjectEvent) -> Result<()> {
+        self.state().project(project_id)?.events.push(event);
+        Ok(())
+    }
+
+    // Get content of doc or file at given path of the project.
+    pub fn content(&self, project_id: &str, path: &str) -> Option<Vec<u8>> {
+        let mut state = self.state();
+        let fake_project = state.project(project_id).ok()?;
+
+        let (_, _, entity) = fake_project
+            .root_folder
+            .find_entities(path)?
+            .into_iter()
+            .find(|(entity_path, _, _)| entity_path == path)?;
+
+        fake_project.contents.get(&entity.id).cloned()
+    }
+
+    // Get id of doc or file at given path of the project.
+    pub fn entity_id(&self, project_id: &str, path: &str) -> Option<String> {
+        let mut state = self.state();
+        let fake_project = state.project(project_id).ok()?;
+
+        fake_project
+            .root_folder
+            .find_entities(path)?
+            .into_iter()
+            .find(|(entity_path, _, _)| entity_path == path)
+            .map(|(_, _, entity)| entity.id.clone())
+    }
+}
+
+impl OverleafApi for FakeOverleaf {
+    fn instance_url(&self) -> &str {
+        BASE_URL
+    }
+
+    async fn get_all_projects(&self) -> Result<ProjectsList> {
+        let projects: Vec<Project> = self
+            .state()
+            .projects
+            .iter()
+            .map(|fake_project| fake_project.project.clone())
+            .collect();
+
+        Ok(ProjectsList {
+            total_size: projects.len() as u64,
+            projects,
+        })
+    }
+
+    fn get_project_details(&self, project_id: &str) -> Result<ProjectDetails> {
+        let mut state = self.state();
+        let fake_project = state.project(project_id)?;
+
+        Ok(ProjectDetails {
+            name: fake_project.project.name.clone(),
+            root_folder: vec![fake_project.root_folder.clone()],
+            compiler: None,
+            image_name: None,
+            root_doc_id: None,
+            spell_check_language: None,
+        })
+    }
+
+    async fn download_project_zip(&self, project_id: String) -> Result<Bytes> {
+        let mut state = self.state();
+
+        if state.invalid_zips {
+            return Ok(Bytes::from_static(b"<html>Service unavailable</html>"));
+        }
+
+        let fake_project = state.project(&project_id)?;
+
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+
+        for (path, _, entity) in fake_project.root_folder.all_entities("") {
+            zip.start_file(path, FileOptions::default())?;
+            zip.write_all(&fake_project.contents[&entity.id])?;
+        }
+
+        Ok(Bytes::from(zip.finish()?.into_inner()))
+    }
+
+    async fn download_doc(&self, project_id: &str, doc_id: &str) -> Result<String> {
+        let mut state = self.state();
+        let fake_project = state.project(project_id)?;
+
+        let content = fake_project
+            .contents
+            .get(doc_id)
+            .context(format!("Failed to download doc {doc_id}."))?;
+
+        String::from_utf8(content.clone()).context("Invalid UTF-8")
+    }
+
+    async fn download_file(&self, project_id: &str, file_id: &str) -> Result<Bytes> {
+        let mut state = self.state();
+        let fake_project = state.project(project_id)?;
+
+        fake_project
+            .contents
+            .get(file_id)
+            .map(|content| Bytes::from(content.clone()))
+            .context(format!("Failed to download file {file_id}."))
+    }
+
+    async fn upload_file(
+        &self,
+        project_id: &str,
+        folder_id: &str,
+        file_name: String,
+        file: Vec<u8>,
+    ) -> Result<()> {
+        let mut state = self.state();
+        let id = state.new_id();
+        let fake_project = state.project(project_id)?;
+
+        let folder = find_folder_by_id_mut(&mut fake_project.root_folder, folder_id).context(
+            format!("Failed to upload file {file_name}: folder not found."),
+        )?;
+
+        if folder.folders.iter().any(|f| f.name == file_name) {
+            bail!("Failed to upload file {file_name}: folder with this name exists.");
+        }
+
+        let replaced: Vec<String> = folder
+            .docs
+            .iter()
+            .chain(&folder.file_refs)
+            .filter(|entity| entity.name == file_name)
+            .map(|entity| entity.id.clone())
+            .collect();
+
+        folder.docs.retain(|doc| doc.name != file_name);
+        folder.file_refs.retain(|file| file.name != file_name);
+
+        let entity = Entity {
+            id: id.clone(),
+            name: file_name,
+        };
+
+        if std::str::from_utf8(&file).is_ok() {
+            folder.docs.push(entity);
+        } else {
+            folder.file_refs.push(entity);
+        }
+
+        for replaced_id in replaced {
+            fake_project.contents.remove(&replaced_id);
+        }
+
+        fake_project.contents.insert(id, file);
+
+        Ok(())
+    }
+
+    async fn update_doc(&self, project_id: &str, doc_id: &str, content: &str) -> Result<()> {
+        let mut state = self.state();
+        let fake_project = state.project(project_id)?;
+
+        if fake_project.root_folder.find_doc_path(doc_id).is_none() {
+            bail!("Failed to update doc {doc_id}: doc not found.");
+        }
+
+        fake_project
+            .contents
+            .insert(doc_id.to_owned(), content.as_bytes().to_vec());
+
+        Ok(())
+    }
+
+    async fn create_folder(
+        &self,
+        project_id: &str,
+        parent_folder_id: &str,
+        name: &str,
+    ) -> Result<Folder> {
+        let mut state = self.state();
+        let id = state.new_id();
+        let fake_project = state.project(project_id)?;
+
+        let parent = find_folder_by_id_mut(&mut fake_project.root_folder, parent_folder_id)
+            .ok_or(anyhow!("Failed to create folder {name}: parent not found."))?;
+
+        if parent.folders.iter().any(|folder| folder.name == name) {
+            bail!("Failed to create folder {name}: folder already exists.");
+        }
+
+        let folder = empty_folder(id, name.to_owned());
+        parent.folders.push(folder.clone());
+
+        Ok(folder)
+    }
+
+    async fn delete_entity(
+        &self,
+        project_id: &str,
+        entity_type: EntityType,
+        entity_id: &str,
+    ) -> Result<()> {
+        let mut state = self.state();
+        let fake_project = state.project(project_id)?;
+
+        let removed = remove_entity(&mut fake_project.root_folder, entity_type, entity_id)
+            .context(format!("Failed to delete entity {entity_id}: not found."))?;
+
+        for id in removed {
+            fake_project.contents.remove(&id);
+        }
+
+        Ok(())
+    }
+
+    fn follow_project(&self, project_id: &str) -> Result<ProjectEvents> {
+        let project = self.get_project_details(project_id)?;
+
+        let mut state = self.state();
+        let fake_project = state.project(project_id)?;
+
+        let mut events: Vec<ProjectEvent> = fake_project
+            .root_folder
+            .all_entities("")
+            .into_iter()
+            .filter(|(_, entity_type, _)| *entity_type == EntityType::Doc)
+            .map(|(_, _, doc)| ProjectEvent::DocJoined {
+                doc_id: doc.id.clone(),
+                content: String::from_utf8_lossy(&fake_project.contents[&doc.id]).into_owned(),
+            })
+            .collect();
+
+        events.insert(0, ProjectEvent::Joined { project });
+        events.append(&mut mem::take(&mut fake_project.events));
+
+        Ok(ProjectEvents::from_events(events))
+    }
+
+    async fn request_compile(
+        &self,
+        project_id: &str,
+        _options: &CompileOptions,
+    ) -> Result<CompileResult> {
+        self.state().project(project_id)?;
+
+        Ok(CompileResult {
+            status: "success".to_owned(),
+            output_files: vec![OutputFile {
+                path: "output.pdf".to_owned(),
+                url: format!("/project/{project_id}/output/output.pdf"),
+                file_type: "pdf".to_owned(),
+                build: None,
+            }],
+            compile_group: None,
+            clsi_server_id: None,
+        })
+    }
+
+    async fn download_output_file(
+        &self,
+        _compile_result: &CompileResult,
+        output_file: &OutputFile,
+    ) -> Result<Bytes> {
+        match output_file.path.as_str() {
+            "output.pdf" => Ok(Bytes::from_static(FAKE_PDF)),
+            path => bail!("Failed to download output file {path}."),
+        }
+    }
+}
+
+// Create local repository of given project with files at given paths, to be synchronized with
+// FakeOverleaf in tests.
+#[cfg(test)]
+pub(crate) fn local_repository(project: &Project, files: &[(&str, &[u8])]) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+
+    std::fs::create_dir(dir.path().join(".olsync")).unwrap();
+    std::fs::write(
+        dir.path().join(".olsync").join("projectinfo"),
+        serde_json::to_string(project).unwrap(),
+    )
+    .unwrap();
+
+    for (path, content) in files {
+        let path = dir.path().join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    dir
+}
@@ -0,0 +1,639 @@
+use crate::{
+    api::OverleafApi,
+    config::Settings,
+    overleaf_client::{Entity, EntityType, Folder, ProjectEvent},
+    progress::{SyncAction, SyncEvent},
+    repository::read_last_pull_timestamp,
+    utils::path_to_str,
+};
+
+use anyhow::{bail, Context, Result};
+use log::info;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+// Entity of project file tree, taken out of its parent folder.
+enum TreeEntity {
+    Doc(Entity),
+    File(Entity),
+    Folder(Folder),
+}
+
+// Find path of doc, file or folder with given id, relative to given folder.
+fn entity_path(folder: &Folder, id: &str) -> Option<String> {
+    let name = folder
+        .docs
+        .iter()
+        .chain(&folder.file_refs)
+        .find(|entity| entity.id == id)
+        .map(|entity| entity.name.clone())
+        .or_else(|| {
+            folder
+                .folders
+                .iter()
+                .find(|subfolder| subfolder.id == id)
+                .map(|subfolder| subfolder.name.clone())
+        });
+
+    name.or_else(|| {
+        folder.folders.iter().find_map(|subfolder| {
+            entity_path(subfolder, id).map(|path| format!("{}/{path}", subfolder.name))
+        })
+    })
+}
+
+fn find_folder_by_id<'a>(folder: &'a Folder, id: &str) -> Option<&'a Folder> {
+    if folder.id == id {
+        return Some(folder);
+    }
+
+    folder
+        .folders
+        .iter()
+        .find_map(|subfolder| find_folder_by_id(subfolder, id))
+}
+
+pub(crate) fn find_folder_by_id_mut<'a>(
+    folder: &'a mut Folder,
+    id: &str,
+) -> Option<&'a mut Folder> {
+    if folder.id == id {
+        return Some(folder);
+    }
+
+    folder
+        .folders
+        .iter_mut()
+        .find_map(|subfolder| find_folder_by_id_mut(subfolder, id))
+}
+
+// Remove entity with given id from the tree and return it.
+fn take_entity(folder: &mut Folder, id: &str) -> Option<TreeEntity> {
+    if let Some(idx) = folder.docs.iter().position(|doc| doc.id == id) {
+        return Some(TreeEntity::Doc(folder.docs.remove(idx)));
+    }
+
+    if let Some(idx) = folder.file_refs.iter().position(|file| file.id == id) {
+        return Some(TreeEntity::File(folder.file_refs.remove(idx)));
+    }
+
+    if let Some(idx) = folder
+        .folders
+        .iter()
+        .position(|subfolder| subfolder.id == id)
+    {
+        return Some(TreeEntity::Folder(folder.folders.remove(idx)));
+    }
+
+    folder
+        .folders
+        .iter_mut()
+        .find_map(|subfolder| take_entity(subfolder, id))
+}
+
+fn rename_entity(folder: &mut Folder, id: &str, new_name: String) {
+    if let Some(entity) = folder
+        .docs
+        .iter_mut()
+        .chain(folder.file_refs.iter_mut())
+        .find(|entity| entity.id == id)
+    {
+        entity.name = new_name;
+    } else if let Some(subfolder) = folder
+        .folders
+        .iter_mut()
+        .find(|subfolder| subfolder.id == id)
+    {
+        subfolder.name = new_name;
+    } else {
+        for subfolder in folder.folders.iter_mut() {
+            rename_entity(subfolder, id, new_name.clone());
+        }
+    }
+}
+
+// Insert entity into folder with given id. Entities of unknown folders are dropped.
+fn insert_entity(folder: &mut Folder, folder_id: &str, entity: TreeEntity) {
+    if let Some(target) = find_folder_by_id_mut(folder, folder_id) {
+        match entity {
+            TreeEntity::Doc(doc) => target.docs.push(doc),
+            TreeEntity::File(file) => target.file_refs.push(file),
+            TreeEntity::Folder(subfolder) => target.folders.push(subfolder),
+        }
+    }
+}
+
+// Content of docs whose local files have not been modified since the last pull, i.e. the
+// content they were pulled with, so that remote changes made before following can be applied.
+fn pulled_docs(
+    root_folder: &Folder,
+    settings: &Settings,
+    repo_root: &Path,
+) -> HashMap<String, String> {
+    let Ok(last_pull) = read_last_pull_timestamp(repo_root) else {
+        return HashMap::new();
+    };
+
+    let modified_after_pull = |path: &Path| {
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .is_none_or(|modified| modified.as_millis() > last_pull as u128)
+    };
+
+    root_folder
+        .all_entities("")
+        .into_iter()
+        .filter(|(_, entity_type, _)| *entity_type == EntityType::Doc)
+        .filter_map(|(path, _, doc)| {
+            let local_path = repo_root.join(settings.local_path(&path)?);
+
+            if modified_after_pull(&local_path) {
+                return None;
+            }
+
+            Some((doc.id.clone(), fs::read_to_string(local_path).ok()?))
+        })
+        .collect()
+}
+
+// Applies changes of remote project to local repository, keeping track of the project file tree
+// and of the last content of every doc known to be the same locally and remotely. Files whose
+// local content differs from it have unsynced local modifications and are never overwritten.
+struct Follower<'a, A: OverleafApi> {
+    overleaf_client: &'a A,
+    project_id: &'a str,
+    repo_root: &'a Path,
+    root_folder: Folder,
+    settings: Settings,
+    synced: HashMap<String, String>,
+    // Docs created remotely while following, which are created locally once their content
+    // arrives.
+    new_docs: HashSet<String>,
+    on_event: &'a (dyn Fn(SyncEvent) + Send + Sync),
+}
+
+impl<A: OverleafApi> Follower<'_, A> {
+    fn report(&self, event: SyncEvent) {
+        (self.on_event)(event)
+    }
+
+    // Path of entity relative to repository root, or None if it is outside of remote directory
+    // mirrored by repository.
+    fn entity_path(&self, id: &str) -> Option<String> {
+        entity_path(&self.root_folder, id).and_then(|path| self.settings.local_path(&path))
+    }
+
+    fn local_path(&self, path: &str) -> PathBuf {
+        self.repo_root.join(path)
+    }
+
+    fn read_local(&self, path: &str) -> Option<String> {
+        fs::read_to_string(self.local_path(path)).ok()
+    }
+
+    fn write_local(&self, path: &str, content: &[u8]) -> Result<()> {
+        let local_path = self.local_path(path);
+
+        if let Some(parent) = local_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(&local_path, content)
+            .context(format!("Failed to save file {}.", path_to_str(&local_path)))
+    }
+
+    fn is_doc_unmodified(&self, doc_id: &str, path: &str) -> bool {
+        match (self.read_local(path), self.synced.get(doc_id)) {
+            (None, _) => true,
+            (Some(local), Some(synced)) => local == *synced,
+            (Some(_), None) => false,
+        }
+    }
+
+    // Check whether entity can be moved or removed locally without losing local modifications.
+    // Binary files are never modified locally by follow, so they are always considered synced.
+    fn is_unmodified(&self, entity_id: &str, path: &str) -> bool {
+        let entities = match find_folder_by_id(&self.root_folder, entity_id) {
+            Some(folder) => folder.all_entities(path),
+            None => self
+                .root_folder
+                .all_entities("")
+                .into_iter()
+                .filter(|(_, _, entity)| entity.id == entity_id)
+                .map(|(_, entity_type, entity)| (path.to_owned(), entity_type, entity))
+                .collect(),
+        };
+
+        entities.iter().all(|(path, entity_type, entity)| {
+            *entity_type == EntityType::File || self.is_doc_unmodified(&entity.id, path)
+        })
+    }
+
+    // Apply current remote content of doc to local file, unless the file was modified locally.
+    fn update_doc(&mut self, doc_id: String, content: String) -> Result<()> {
+        let Some(path) = self.entity_path(&doc_id) else {
+            return Ok(());
+        };
+
+        let local = self.read_local(&path);
+
+        if local.as_ref() == Some(&content) {
+            self.synced.insert(doc_id, content);
+            return Ok(());
+        }
+
+        let overwrite = match (&local, self.synced.get(&doc_id)) {
+            (None, _) => self.new_docs.remove(&doc_id),
+            (Some(local), Some(synced)) => local == synced,
+            (Some(_), None) => false,
+        };
+
+        if overwrite {
+            self.write_local(&path, content.as_bytes())?;
+            self.synced.insert(doc_id, content);
+
+            self.report(SyncEvent::new(SyncAction::Pulled, path));
+        } else {
+            let reason = if local.is_some() {
+                "modified locally"
+            } else {
+                "deleted locally"
+            };
+
+            self.report(SyncEvent::with_detail(
+                SyncAction::Skipped,
+                path,
+                reason.to_owned(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn add_file(&mut self, folder_id: String, file: Entity) -> Result<()> {
+        let file_id = file.id.clone();
+        insert_entity(&mut self.root_folder, &folder_id, TreeEntity::File(file));
+
+        let Some(path) = self.entity_path(&file_id) else {
+            return Ok(());
+        };
+
+        if self.local_path(&path).exists() {
+            self.report(SyncEvent::with_detail(
+                SyncAction::Skipped,
+                path,
+                "exists locally".to_owned(),
+            ));
+            return Ok(());
+        }
+
+        let content = self
+            .overleaf_client
+            .download_file(self.project_id, &file_id)
+            .await?;
+
+        self.write_local(&path, &content)?;
+
+        self.report(SyncEvent::new(SyncAction::Pulled, path));
+
+        Ok(())
+    }
+
+    // Move local file or folder after the entity was renamed or moved remotely.
+    fn move_local(&self, old_path: &str, new_path: &str, unmodified: bool) -> Result<()> {
+        let old_local = self.local_path(old_path);
+        let new_local = self.local_path(new_path);
+
+        if !old_local.exists() || old_path == new_path {
+            return Ok(());
+        }
+
+        if new_local.exists() {
+            self.report(SyncEvent::with_detail(
+                SyncAction::Skipped,
+                old_path.to_owned(),
+                format!("{new_path} exists locally"),
+            ));
+            return Ok(());
+        }
+
+        if !unmodified {
+            self.report(SyncEvent::with_detail(
+                SyncAction::Skipped,
+                old_path.to_owned(),
+                "modified locally".to_owned(),
+            ));
+            return Ok(());
+        }
+
+        if let Some(parent) = new_local.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::rename(&old_local, &new_local)
+            .context(format!("Failed to move {old_path} to {new_path}."))?;
+
+        self.report(SyncEvent::with_detail(
+            SyncAction::Moved,
+            old_path.to_owned(),
+            new_path.to_owned(),
+        ));
+
+        Ok(())
+    }
+
+    fn remove(&mut self, entity_id: String) -> Result<()> {
+        let Some(path) = self.entity_path(&entity_id) else {
+            return Ok(());
+        };
+
+        let unmodified = self.is_unmodified(&entity_id, &path);
+
+        match take_entity(&mut self.root_folder, &entity_id) {
+            Some(TreeEntity::Doc(doc)) => {
+                self.synced.remove(&doc.id);
+            }
+            Some(TreeEntity::Folder(folder)) => {
+                for (_, _, entity) in folder.all_entities("") {
+                    self.synced.remove(&entity.id);
+                }
+            }
+            _ => {}
+        }
+
+        let local_path = self.local_path(&path);
+
+        if !local_path.exists() {
+            return Ok(());
+        }
+
+        if !unmodified {
+            self.report(SyncEvent::with_detail(
+                SyncAction::Skipped,
+                path,
+                "modified locally".to_owned(),
+            ));
+            return Ok(());
+        }
+
+        if local_path.is_dir() {
+            fs::remove_dir_all(&local_path)
+        } else {
+            fs::remove_file(&local_path)
+        }
+        .context(format!("Failed to remove {path}."))?;
+
+        self.report(SyncEvent::new(SyncAction::Removed, path));
+
+        Ok(())
+    }
+
+    async fn handle_event(&mut self, event: ProjectEvent) -> Result<()> {
+        match event {
+            ProjectEvent::Joined { .. } => {}
+            ProjectEvent::DocJoined { doc_id, content }
+            | ProjectEvent::DocUpdated { doc_id, content } => self.update_doc(doc_id, content)?,
+            ProjectEvent::NewDoc { folder_id, doc } => {
+                self.new_docs.insert(doc.id.clone());
+                insert_entity(&mut self.root_folder, &folder_id, TreeEntity::Doc(doc));
+            }
+            ProjectEvent::NewFile { folder_id, file } => self.add_file(folder_id, file).await?,
+            ProjectEvent::NewFolder { folder_id, folder } => {
+                let new_folder_id = folder.id.clone();
+                insert_entity(
+                    &mut self.root_folder,
+                    &folder_id,
+                    TreeEntity::Folder(folder),
+                );
+
+                if let Some(path) = self.entity_path(&new_folder_id) {
+                    fs::create_dir_all(self.local_path(&path))?;
+                }
+            }
+            ProjectEvent::Rename {
+                entity_id,
+                new_name,
+            } => {
+                let Some(old_path) = self.entity_path(&entity_id) else {
+                    return Ok(());
+                };
+
+                let unmodified = self.is_unmodified(&entity_id, &old_path);
+
+                rename_entity(&mut self.root_folder, &entity_id, new_name);
+
+                if let Some(new_path) = self.entity_path(&entity_id) {
+                    self.move_local(&old_path, &new_path, unmodified)?;
+                }
+            }
+            ProjectEvent::Move {
+                entity_id,
+                folder_id,
+            } => {
+                let Some(old_path) = self.entity_path(&entity_id) else {
+                    return Ok(());
+                };
+
+                let unmodified = self.is_unmodified(&entity_id, &old_path);
+
+                if let Some(entity) = take_entity(&mut self.root_folder, &entity_id) {
+                    insert_entity(&mut self.root_folder, &folder_id, entity);
+                }
+
+                if let Some(new_path) = self.entity_path(&entity_id) {
+                    self.move_local(&old_path, &new_path, unmodified)?;
+                }
+            }
+            ProjectEvent::Remove { entity_id } => self.remove(entity_id)?,
+        }
+
+        Ok(())
+    }
+}
+
+// Follow remote project and apply changes of collaborators to local repository as they happen,
+// until interrupted or disconnected. Every applied or skipped change is passed to `on_event`.
+pub async fn follow_project_changes<A: OverleafApi>(
+    overleaf_client: &A,
+    project_id: &str,
+    repo_root: &Path,
+    on_event: &(dyn Fn(SyncEvent) + Send + Sync),
+) -> Result<()> {
+    let settings = Settings::load(Some(repo_root))?;
+    let mut events = overleaf_client.follow_project(project_id)?;
+    let mut follower: Option<Follower<A>> = None;
+
+    while let Some(event) = events.next().await? {
+        if let ProjectEvent::Joined { project } = event {
+            let root_folder = project
+                .root_folder
+                .into_iter()
+                .next()
+                .context("Project details do not contain root folder.")?;
+
+            // Content synced while following before reconnecting is newer than the pulled one.
+            let mut synced = pulled_docs(&root_folder, &settings, repo_root);
+            synced.extend(
+                follower
+                    .take()
+                    .map(|follower| follower.synced)
+                    .unwrap_or_default(),
+            );
+
+            follower = Some(Follower {
+                overleaf_client,
+                project_id,
+                repo_root,
+                root_folder,
+                settings: settings.clone(),
+                synced,
+                new_docs: HashSet::new(),
+                on_event,
+            });
+
+            info!("Following project {}. Press Ctrl+C to stop.", project.name);
+            continue;
+        }
+
+        let Some(follower) = follower.as_mut() else {
+            bail!("Received project event before joining the project.")
+        };
+
+        if let Err(err) = follower.handle_event(event).await {
+            (follower.on_event)(SyncEvent::with_detail(
+                SyncAction::Failed,
+                String::new(),
+                err.to_string(),
+            ));
+        }
+    }
+
+    bail!("Connection to Overleaf was closed.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fake::{local_repository, FakeOverleaf},
+        overleaf_client::Project,
+        progress::NoProgress,
+        repository::pull_project,
+    };
+    use std::{sync::Mutex, thread, time::Duration};
+
+    // Follow project until all queued events are applied and return reported sync events.
+    async fn follow(
+        overleaf: &FakeOverleaf,
+        project: &Project,
+        repo_root: &Path,
+    ) -> Vec<SyncEvent> {
+        let events = Mutex::new(Vec::new());
+        let on_event = |event| events.lock().unwrap().push(event);
+
+        let result = follow_project_changes(overleaf, &project.id, repo_root, &on_event).await;
+        assert!(result.is_err());
+
+        events.into_inner().unwrap()
+    }
+
+    #[tokio::test]
+    async fn conflicting_local_changes_are_never_overwritten() {
+        let overleaf = FakeOverleaf::new();
+        let project = overleaf.add_project("thesis");
+        let doc_id = overleaf.add_doc(&project.id, "main.tex", "remote").unwrap();
+
+        let repo = local_repository(&project, &[("main.tex", b"local")]);
+
+        overleaf
+            .queue_event(
+                &project.id,
+                ProjectEvent::DocUpdated {
+                    doc_id: doc_id.clone(),
+                    content: "remote edit".to_owned(),
+                },
+            )
+            .unwrap();
+        overleaf
+            .queue_event(&project.id, ProjectEvent::Remove { entity_id: doc_id })
+            .unwrap();
+
+        let events = follow(&overleaf, &project, repo.path()).await;
+
+        assert_eq!(events.len(), 3);
+        assert!(events
+            .iter()
+            .all(|event| event.action == SyncAction::Skipped
+                && event.detail.as_deref() == Some("modified locally")));
+        assert_eq!(
+            fs::read_to_string(repo.path().join("main.tex")).unwrap(),
+            "local"
+        );
+    }
+
+    #[tokio::test]
+    async fn remote_edits_are_applied_to_files_unchanged_since_pull() {
+        let overleaf = FakeOverleaf::new();
+        let project = overleaf.add_project("thesis");
+        let main_id = overleaf.add_doc(&project.id, "main.tex", "v1").unwrap();
+        let notes_id = overleaf.add_doc(&project.id, "notes.tex", "v1").unwrap();
+
+        let repo = local_repository(&project, &[]);
+        pull_project(&overleaf, &project.id, repo.path(), &NoProgress)
+            .await
+            .unwrap();
+
+        // Local edit has to be later than the pull, which is recorded in milliseconds.
+        thread::sleep(Duration::from_millis(10));
+        fs::write(repo.path().join("notes.tex"), "local").unwrap();
+
+        // Edits made by collaborators before and while following.
+        overleaf
+            .update_doc(&project.id, &main_id, "v2")
+            .await
+            .unwrap();
+        overleaf
+            .update_doc(&project.id, &notes_id, "v2")
+            .await
+            .unwrap();
+        overleaf
+            .queue_event(
+                &project.id,
+                ProjectEvent::DocUpdated {
+                    doc_id: main_id,
+                    content: "v3".to_owned(),
+                },
+            )
+            .unwrap();
+
+        let events = follow(&overleaf, &project, repo.path()).await;
+
+        let actions: Vec<_> = events
+            .iter()
+            .map(|event| (event.action, event.path.as_str()))
+            .collect();
+
+        assert_eq!(
+            actions,
+            vec![
+                (SyncAction::Pulled, "main.tex"),
+                (SyncAction::Skipped, "notes.tex"),
+                (SyncAction::Pulled, "main.tex"),
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(repo.path().join("main.tex")).unwrap(),
+            "v3"
+        );
+        assert_eq!(
+            fs::read_to_string(repo.path().join("notes.tex")).unwrap(),
+            "local"
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use log::{debug, log_enabled, Level};
+use reqwest::{header::HeaderMap, Request, RequestBuilder, Response};
+use std::{future::Future, time::Instant};
+
+// Headers carrying session secrets, which must not end up in logs attached to bug reports.
+const SECRET_HEADERS: [&str; 4] = ["cookie", "set-cookie", "authorization", "x-csrf-token"];
+
+// Format headers one per line, with values of secret headers redacted.
+fn format_headers(headers: &HeaderMap) -> String {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if SECRET_HEADERS.contains(&name.as_str()) {
+                "[redacted]"
+            } else {
+                value.to_str().unwrap_or("[binary]")
+            };
+
+            format!("\n{name}: {value}")
+        })
+        .collect()
+}
+
+fn trace_request(request: &Request) {
+    debug!(
+        "--> {} {}{}",
+        request.method(),
+        request.url(),
+        format_headers(request.headers())
+    );
+}
+
+fn trace_response(response: &Response, started: Instant) {
+    debug!(
+        "<-- {} {} ({} ms){}",
+        response.status(),
+        response.url(),
+        started.elapsed().as_millis(),
+        format_headers(response.headers())
+    );
+}
+
+// Sending requests with their tracing in debug logs.
+pub(crate) trait SendTraced {
+    fn send_traced(self) -> impl Future<Output = reqwest::Result<Response>> + Send;
+}
+
+impl SendTraced for RequestBuilder {
+    async fn send_traced(self) -> reqwest::Result<Response> {
+        let (client, request) = self.build_split();
+        let request = request?;
+
+        if log_enabled!(Level::Debug) {
+            trace_request(&request);
+        }
+
+        let started = Instant::now();
+        let result = client.execute(request).await;
+
+        match &result {
+            Ok(response) => trace_response(response, started),
+            Err(err) => debug!("<-- Request failed: {err}"),
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderValue, CONTENT_TYPE, COOKIE, SET_COOKIE};
+
+    #[test]
+    fn secret_headers_are_redacted() {
+        let mut headers = HeaderMap::new();
+        headers.insert(COOKIE, HeaderValue::from_static("overleaf_session2=secret"));
+        headers.insert(SET_COOKIE, HeaderValue::from_static("GCLB=secret"));
+        headers.insert("X-CSRF-TOKEN", HeaderValue::from_static("secret"));
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        let formatted = format_headers(&headers);
+
+        assert!(!formatted.contains("secret"));
+        assert!(formatted.contains("cookie: [redacted]"));
+        assert!(formatted.contains("content-type: application/json"));
+    }
+}
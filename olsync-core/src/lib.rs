@@ -0,0 +1,19 @@
+pub mod api;
+pub mod archive;
+pub mod auth;
+pub mod backup;
+pub mod batch;
+pub mod config;
+pub mod constants;
+pub mod daemon;
+pub mod error;
+#[cfg(any(test, feature = "fake"))]
+pub mod fake;
+pub mod follow;
+pub mod http_trace;
+pub mod overleaf_client;
+pub mod paths;
+pub mod progress;
+pub mod repository;
+pub mod utils;
+pub mod watch;
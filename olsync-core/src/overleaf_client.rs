@@ -0,0 +1,1421 @@
+use anyhow::{anyhow, bail, Context, Result};
+use bytes::Bytes;
+use chrono::{TimeZone, Utc};
+use headless_chrome::protocol::cdp::{types::JsFloat, Network::Cookie};
+use reqwest::{
+    header::{HeaderMap, HeaderValue, COOKIE},
+    multipart, Client, Response, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use soup::prelude::*;
+use std::{
+    collections::VecDeque,
+    process::{Command, Stdio},
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines},
+    process::{Child, ChildStdout},
+};
+
+use crate::{
+    constants::{
+        BASE_URL, BASE_URL_ENV, COMPILE_PROJECT_URL, CREATE_FOLDER_URL, DELETE_ENTITY_URL,
+        DOWNLOAD_DOC_URL, DOWNLOAD_FILE_URL, DOWNLOAD_PROJECT_URL, PROJECTS_URL,
+        PROJECT_ADMIN_SETTINGS_URL, PROJECT_INVITES_URL, PROJECT_INVITE_URL, PROJECT_LABELS_URL,
+        PROJECT_LABEL_URL, PROJECT_MEMBERS_URL, PROJECT_MEMBER_URL, PROJECT_SETTINGS_URL,
+        PROJECT_TOKENS_URL, PROJECT_UPDATES_URL, PROJECT_VERSION_ZIP_URL, RESTORE_FILE_URL,
+        TAG_URL, TRANSFER_OWNERSHIP_URL, UPLOAD_FILE_URL,
+    },
+    error::OlsyncError,
+    http_trace::SendTraced,
+    utils::overleaf_url,
+};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct OlCookie {
+    pub name: String,
+    pub value: String,
+    pub expires: JsFloat,
+}
+
+impl OlCookie {
+    pub fn from_chrome_cookie(cookie: Cookie) -> Self {
+        OlCookie {
+            name: cookie.name,
+            value: cookie.value,
+            expires: cookie.expires,
+        }
+    }
+
+    pub fn has_expired(&self) -> bool {
+        self.expires <= ((Utc::now().timestamp_millis() / 1000) as f64)
+    }
+
+    pub fn expiry_date_pretty(&self) -> String {
+        Utc.timestamp_opt(self.expires as i64, 0)
+            .unwrap()
+            .to_string()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SessionInfo {
+    pub email: String,
+    pub session_cookie: OlCookie,
+    pub gclb_cookie: OlCookie,
+    pub csrf_token: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Tag {
+    #[serde(rename(deserialize = "_id"))]
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub project_ids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectsList {
+    pub total_size: u64,
+    pub projects: Vec<Project>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Entity {
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Folder {
+    #[serde(rename = "_id")]
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub docs: Vec<Entity>,
+    #[serde(default)]
+    pub file_refs: Vec<Entity>,
+    #[serde(default)]
+    pub folders: Vec<Folder>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EntityType {
+    Doc,
+    File,
+    Folder,
+}
+
+impl Folder {
+    // Find doc by its path relative to this folder, e.g. chapters/intro.tex.
+    pub fn find_doc(&self, path: &str) -> Option<&Entity> {
+        match path.split_once('/') {
+            Some((folder_name, rest)) => self
+                .folders
+                .iter()
+                .find(|folder| folder.name == folder_name)
+                .and_then(|folder| folder.find_doc(rest)),
+            None => self.docs.iter().find(|doc| doc.name == path),
+        }
+    }
+
+    // Find subfolder by its path relative to this folder. Empty path gives this folder.
+    pub fn find_folder(&self, path: &str) -> Option<&Folder> {
+        if path.is_empty() {
+            return Some(self);
+        }
+
+        let (folder_name, rest) = path.split_once('/').unwrap_or((path, ""));
+
+        self.folders
+            .iter()
+            .find(|folder| folder.name == folder_name)
+            .and_then(|folder| folder.find_folder(rest))
+    }
+
+    // Collect all docs and files in this folder and its subfolders, with paths prefixed by given
+    // prefix.
+    pub fn all_entities(&self, prefix: &str) -> Vec<(String, EntityType, &Entity)> {
+        let join = |name: &str| {
+            if prefix.is_empty() {
+                name.to_owned()
+            } else {
+                format!("{prefix}/{name}")
+            }
+        };
+
+        let mut entities: Vec<_> = self
+            .docs
+            .iter()
+            .map(|doc| (join(&doc.name), EntityType::Doc, doc))
+            .chain(
+                self.file_refs
+                    .iter()
+                    .map(|file| (join(&file.name), EntityType::File, file)),
+            )
+            .collect();
+
+        for folder in &self.folders {
+            entities.extend(folder.all_entities(&join(&folder.name)));
+        }
+
+        entities
+    }
+
+    // Find docs and files by path relative to this folder. Path pointing to a folder gives all
+    // entities inside of it.
+    pub fn find_entities(&self, path: &str) -> Option<Vec<(String, EntityType, &Entity)>> {
+        let path = path.trim_start_matches("./").trim_end_matches('/');
+        let (parent_path, name) = path.rsplit_once('/').unwrap_or(("", path));
+
+        let parent = self.find_folder(parent_path)?;
+
+        if let Some(doc) = parent.docs.iter().find(|doc| doc.name == name) {
+            return Some(vec![(path.to_owned(), EntityType::Doc, doc)]);
+        }
+
+        if let Some(file) = parent.file_refs.iter().find(|file| file.name == name) {
+            return Some(vec![(path.to_owned(), EntityType::File, file)]);
+        }
+
+        parent
+            .find_folder(name)
+            .map(|folder| folder.all_entities(path))
+    }
+
+    // Find path of doc with given id, relative to this folder.
+    pub fn find_doc_path(&self, doc_id: &str) -> Option<String> {
+        if let Some(doc) = self.docs.iter().find(|doc| doc.id == doc_id) {
+            return Some(doc.name.clone());
+        }
+
+        self.folders.iter().find_map(|folder| {
+            folder
+                .find_doc_path(doc_id)
+                .map(|path| format!("{}/{path}", folder.name))
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectDetails {
+    pub name: String,
+    pub root_folder: Vec<Folder>,
+    pub compiler: Option<String>,
+    pub image_name: Option<String>,
+    #[serde(rename = "rootDoc_id")]
+    pub root_doc_id: Option<String>,
+    pub spell_check_language: Option<String>,
+}
+
+// Change of remote project, as reported by socket.io client following the project.
+#[derive(Debug, Deserialize)]
+#[serde(
+    tag = "event",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ProjectEvent {
+    Joined {
+        project: ProjectDetails,
+    },
+    DocJoined {
+        doc_id: String,
+        content: String,
+    },
+    DocUpdated {
+        doc_id: String,
+        content: String,
+    },
+    NewDoc {
+        folder_id: String,
+        doc: Entity,
+    },
+    NewFile {
+        folder_id: String,
+        file: Entity,
+    },
+    NewFolder {
+        folder_id: String,
+        folder: Folder,
+    },
+    Rename {
+        entity_id: String,
+        new_name: String,
+    },
+    Move {
+        entity_id: String,
+        folder_id: String,
+    },
+    Remove {
+        entity_id: String,
+    },
+}
+
+// Stream of events of followed project. The connection is closed when it is dropped.
+pub struct ProjectEvents {
+    _child: Option<Child>,
+    lines: Option<Lines<BufReader<ChildStdout>>>,
+    // Events known upfront, returned before the ones read from socket.io client.
+    queued: VecDeque<ProjectEvent>,
+}
+
+impl ProjectEvents {
+    // Stream of given events, closed after the last one.
+    #[cfg(any(test, feature = "fake"))]
+    pub(crate) fn from_events(events: Vec<ProjectEvent>) -> Self {
+        ProjectEvents {
+            _child: None,
+            lines: None,
+            queued: events.into(),
+        }
+    }
+
+    // Wait for next event. Returns None once the connection is closed.
+    pub async fn next(&mut self) -> Result<Option<ProjectEvent>> {
+        if let Some(event) = self.queued.pop_front() {
+            return Ok(Some(event));
+        }
+
+        let Some(lines) = self.lines.as_mut() else {
+            return Ok(None);
+        };
+
+        match lines.next_line().await? {
+            Some(line) => serde_json::from_str(&line).map(Some).map_err(|e| {
+                anyhow!(format!(
+                    "Failed to deserialize project event with error: {e}."
+                ))
+            }),
+            None => Ok(None),
+        }
+    }
+}
+
+// Project settings as shown in the settings menu of Overleaf editor.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSettings {
+    pub name: String,
+    pub compiler: Option<String>,
+    pub image_name: Option<String>,
+    pub root_doc_path: Option<String>,
+    pub spell_check_language: Option<String>,
+}
+
+impl ProjectSettings {
+    pub fn from_details(project_details: &ProjectDetails) -> Self {
+        let root_doc_path = project_details
+            .root_doc_id
+            .as_ref()
+            .and_then(|id| project_details.root_folder.first()?.find_doc_path(id));
+
+        ProjectSettings {
+            name: project_details.name.clone(),
+            compiler: project_details.compiler.clone(),
+            image_name: project_details.image_name.clone(),
+            root_doc_path,
+            spell_check_language: project_details.spell_check_language.clone(),
+        }
+    }
+}
+
+// Changes to project settings. Settings which are None are left unchanged.
+#[derive(Debug, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSettingsUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compiler: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root_doc_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spell_check_language: Option<String>,
+}
+
+impl ProjectSettingsUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.compiler.is_none()
+            && self.image_name.is_none()
+            && self.root_doc_id.is_none()
+            && self.spell_check_language.is_none()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct OutputFile {
+    pub path: String,
+    pub url: String,
+    #[serde(rename = "type")]
+    pub file_type: String,
+    pub build: Option<String>,
+}
+
+// Options of remote compilation. Without root doc id, the main document set in project settings
+// is compiled.
+#[derive(Debug, Default, Clone)]
+pub struct CompileOptions {
+    pub root_doc_id: Option<String>,
+    pub draft: bool,
+    pub stop_on_first_error: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CompileResult {
+    pub status: String,
+    #[serde(default)]
+    pub output_files: Vec<OutputFile>,
+    pub compile_group: Option<String>,
+    pub clsi_server_id: Option<String>,
+}
+
+impl CompileResult {
+    pub fn is_success(&self) -> bool {
+        self.status == "success"
+    }
+
+    // Find output file by its path, e.g. output.pdf. The `output.` prefix can be omitted.
+    pub fn output_file(&self, path: &str) -> Option<&OutputFile> {
+        let prefixed_path = format!("output.{path}");
+
+        self.output_files
+            .iter()
+            .find(|file| file.path == path)
+            .or_else(|| {
+                self.output_files
+                    .iter()
+                    .find(|file| file.path == prefixed_path)
+            })
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct HistoryUser {
+    pub id: Option<String>,
+    pub email: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+}
+
+impl HistoryUser {
+    pub fn display_name(&self) -> String {
+        let full_name = [&self.first_name, &self.last_name]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        if !full_name.is_empty() {
+            full_name
+        } else {
+            self.email.clone().unwrap_or("Unknown user".to_owned())
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct HistoryUpdateMeta {
+    #[serde(default)]
+    pub users: Vec<Option<HistoryUser>>,
+    pub start_ts: i64,
+    pub end_ts: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PathnameChange {
+    pub pathname: String,
+    pub new_pathname: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectOp {
+    pub add: Option<PathnameChange>,
+    pub rename: Option<PathnameChange>,
+    pub remove: Option<PathnameChange>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Label {
+    pub id: String,
+    pub comment: String,
+    pub version: u64,
+    pub created_at: Option<String>,
+    pub user_display_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryUpdate {
+    #[serde(rename(deserialize = "fromV"))]
+    pub from_version: u64,
+    #[serde(rename(deserialize = "toV"))]
+    pub to_version: u64,
+    pub meta: HistoryUpdateMeta,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    #[serde(default)]
+    pub pathnames: Vec<String>,
+    #[serde(default, rename(deserialize = "project_ops"))]
+    pub project_ops: Vec<ProjectOp>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryUpdatesPage {
+    pub updates: Vec<HistoryUpdate>,
+    pub next_before_timestamp: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Member {
+    #[serde(rename(deserialize = "_id"))]
+    pub id: String,
+    pub email: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub privileges: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MembersList {
+    pub members: Vec<Member>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Invite {
+    #[serde(rename(deserialize = "_id"))]
+    pub id: String,
+    pub email: String,
+    pub privileges: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InvitesList {
+    pub invites: Vec<Invite>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareTokens {
+    pub read_only: Option<String>,
+    pub read_and_write: Option<String>,
+}
+
+// Whether request was rejected because of missing or expired session. Overleaf redirects such
+// requests of pages to the login page.
+fn is_unauthorized(res: &Response) -> bool {
+    res.status() == StatusCode::UNAUTHORIZED || res.url().path() == "/login"
+}
+
+// Return response if its status is successful, otherwise fail with given message followed by
+// response status and body.
+async fn ensure_success(res: Response, message: String) -> Result<Response> {
+    if is_unauthorized(&res) {
+        bail!(OlsyncError::AuthExpired);
+    }
+
+    if res.status().is_success() {
+        return Ok(res);
+    }
+
+    bail!(OlsyncError::RemoteRejected {
+        action: message,
+        status: res.status(),
+        body: String::from_utf8(res.bytes().await?.to_vec())
+            .unwrap_or("Invalid UTF-8 response.".to_owned()),
+    })
+}
+
+pub struct OverleafClient {
+    session_info: SessionInfo,
+    reqwest_client: Client,
+    instance_url: String,
+}
+
+impl OverleafClient {
+    // Create client of Overleaf instance with given base URL, e.g. https://www.overleaf.com.
+    pub fn new(session_info: SessionInfo, instance_url: &str) -> Result<Self> {
+        let mut headers = HeaderMap::new();
+
+        headers.insert(
+            COOKIE,
+            HeaderValue::from_str(
+                &[&session_info.session_cookie]
+                    .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+                    .join(";"),
+            )
+            .context("Failed to build Cookie header for Overleaf client.")?,
+        );
+
+        headers.insert(
+            "X-CSRF-TOKEN",
+            HeaderValue::from_str(&session_info.csrf_token)
+                .context("Failed to build X-CSRF-TOKEN header for Overleaf client.")?,
+        );
+
+        let reqwest_client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .context("Failed to build reqwest client.")?;
+
+        Ok(Self {
+            session_info,
+            reqwest_client,
+            instance_url: instance_url.to_owned(),
+        })
+    }
+
+    // Base URL of Overleaf instance of the client.
+    pub fn instance_url(&self) -> &str {
+        &self.instance_url
+    }
+
+    // Resolve URL of Overleaf endpoint on instance of the client.
+    pub fn url(&self, url: &str) -> String {
+        overleaf_url(&self.instance_url, url)
+    }
+
+    // Fetch all projects.
+    pub async fn get_all_projects(&self) -> Result<ProjectsList> {
+        let projects_list_content = self
+            .get_projects_page_meta("ol-prefetchedProjectsBlob")
+            .await
+            .context("Failed to retrieve list of projects. Please try again.")?;
+
+        serde_json::from_str(projects_list_content.as_str()).map_err(|e| {
+            anyhow!(format!(
+                "Failed to deserialize projects list with error: {e}."
+            ))
+        })
+    }
+
+    // Fetch all tags together with projects they contain.
+    pub async fn get_all_tags(&self) -> Result<Vec<Tag>> {
+        let tags_content = self
+            .get_projects_page_meta("ol-tags")
+            .await
+            .context("Failed to retrieve list of tags. Please try again.")?;
+
+        serde_json::from_str(tags_content.as_str())
+            .map_err(|e| anyhow!(format!("Failed to deserialize tags with error: {e}.")))
+    }
+
+    // Fetch projects page and read content of meta tag with given name.
+    async fn get_projects_page_meta(&self, meta_name: &str) -> Result<String> {
+        let res = self
+            .reqwest_client
+            .get(self.url(PROJECTS_URL))
+            .send_traced()
+            .await?;
+
+        let projects_page_content = ensure_success(res, "Failed to fetch projects page".to_owned())
+            .await?
+            .text()
+            .await?;
+
+        Soup::new(projects_page_content.as_str())
+            .tag("meta")
+            .attr("name", meta_name.to_owned())
+            .find()
+            .and_then(|tag| tag.get("content"))
+            .context(format!("Meta tag {meta_name} not found in projects page."))
+    }
+
+    // Fetch specified project by name.
+    pub async fn get_project_by_name(&self, project_name: &String) -> Result<Project> {
+        let mut projects: Vec<Project> = self
+            .get_all_projects()
+            .await?
+            .projects
+            .into_iter()
+            .filter(|project| project.name == *project_name)
+            .collect();
+
+        match projects.len() {
+            0 => bail!(OlsyncError::ProjectNotFound(project_name.clone())),
+            1 => Ok(projects.remove(0)),
+            _ => bail!(OlsyncError::AmbiguousProjectName {
+                name: project_name.clone(),
+                ids: projects.into_iter().map(|project| project.id).collect(),
+            }),
+        }
+    }
+
+    // Fetch specified project by id.
+    pub async fn get_project_by_id(&self, project_id: &String) -> Result<Project> {
+        self.get_all_projects()
+            .await?
+            .projects
+            .into_iter()
+            .rfind(|project| project.id == *project_id)
+            .ok_or(anyhow!(OlsyncError::ProjectNotFound(project_id.clone())))
+    }
+
+    // Fetch specified project info.
+    pub fn get_project_details(&self, project_id: &str) -> Result<ProjectDetails> {
+        let output = String::from_utf8(
+            Command::new("olsync-rs-socketio-client")
+                .env(BASE_URL_ENV, self.url(BASE_URL))
+                .args([
+                    self.session_info.gclb_cookie.value.as_str(),
+                    self.session_info.session_cookie.value.as_str(),
+                    project_id,
+                ])
+                .output()
+                .context(format!(
+                    "Failed to obtain project info for project {project_id}."
+                ))?
+                .stdout,
+        )
+        .context("Invalid UTF-8")?
+        .replace("'", "\"")
+        .replace("None", "null")
+        .replace("True", "true")
+        .replace("False", "false");
+
+        serde_json::from_str(output.as_str()).map_err(|e| {
+            anyhow!(format!(
+                "Failed to deserialize project details with error: {e}."
+            ))
+        })
+    }
+
+    // Replace content of existing doc through the document update channel, so that the change
+    // shows up in history as an edit and open editors of collaborators stay in sync.
+    pub async fn update_doc(&self, project_id: &str, doc_id: &str, content: &str) -> Result<()> {
+        let mut child = tokio::process::Command::new("olsync-rs-socketio-client")
+            .env(BASE_URL_ENV, self.url(BASE_URL))
+            .args([
+                self.session_info.gclb_cookie.value.as_str(),
+                self.session_info.session_cookie.value.as_str(),
+                project_id,
+                "--update-doc",
+                doc_id,
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context(format!("Failed to update doc {doc_id}."))?;
+
+        // Stdin is closed once written, so that the client reads the whole content.
+        {
+            let mut stdin = child
+                .stdin
+                .take()
+                .context("Failed to open stdin of socket.io client.")?;
+
+            stdin.write_all(content.as_bytes()).await?;
+        }
+
+        let output = child.wait_with_output().await?;
+
+        if !output.status.success() {
+            bail!(
+                "Failed to update doc {doc_id} with error:\n{}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+        }
+
+        Ok(())
+    }
+
+    // Join specified project and listen for its changes made by collaborators.
+    pub fn follow_project(&self, project_id: &str) -> Result<ProjectEvents> {
+        let mut child = tokio::process::Command::new("olsync-rs-socketio-client")
+            .env(BASE_URL_ENV, self.url(BASE_URL))
+            .args([
+                self.session_info.gclb_cookie.value.as_str(),
+                self.session_info.session_cookie.value.as_str(),
+                project_id,
+                "--follow",
+            ])
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .context(format!("Failed to follow project {project_id}."))?;
+
+        let stdout = child
+            .stdout
+            .take()
+            .context("Failed to open stdout of socket.io client.")?;
+
+        Ok(ProjectEvents {
+            _child: Some(child),
+            lines: Some(BufReader::new(stdout).lines()),
+            queued: VecDeque::new(),
+        })
+    }
+
+    // Download specified project as zip.
+    pub async fn download_project_zip(&self, project_id: String) -> Result<Bytes> {
+        let res = self
+            .reqwest_client
+            .get(
+                self.url(DOWNLOAD_PROJECT_URL)
+                    .replace("{}", project_id.as_str()),
+            )
+            .send_traced()
+            .await?;
+
+        let res = ensure_success(
+            res,
+            format!("Failed to download project {project_id} as zip"),
+        )
+        .await?;
+
+        res.bytes().await.context(format!(
+            "Error occured while downloading project {project_id} as zip.",
+        ))
+    }
+
+    // Download text of a single doc of specified project.
+    pub async fn download_doc(&self, project_id: &str, doc_id: &str) -> Result<String> {
+        let res = self
+            .reqwest_client
+            .get(
+                self.url(DOWNLOAD_DOC_URL)
+                    .replacen("{}", project_id, 1)
+                    .replacen("{}", doc_id, 1),
+            )
+            .send_traced()
+            .await?;
+
+        let res = ensure_success(res, format!("Failed to download doc {doc_id}")).await?;
+
+        res.text()
+            .await
+            .context(format!("Error occured while downloading doc {doc_id}."))
+    }
+
+    // Download a single binary file of specified project.
+    pub async fn download_file(&self, project_id: &str, file_id: &str) -> Result<Bytes> {
+        let res = self
+            .reqwest_client
+            .get(
+                self.url(DOWNLOAD_FILE_URL)
+                    .replacen("{}", project_id, 1)
+                    .replacen("{}", file_id, 1),
+            )
+            .send_traced()
+            .await?;
+
+        let res = ensure_success(res, format!("Failed to download file {file_id}")).await?;
+
+        res.bytes()
+            .await
+            .context(format!("Error occured while downloading file {file_id}."))
+    }
+
+    // Upload file to specified filed in remote project.
+    pub async fn upload_file(
+        &self,
+        project_id: &str,
+        folder_id: &str,
+        file_name: String,
+        file: Vec<u8>,
+    ) -> Result<()> {
+        let file_part = multipart::Part::bytes(file).file_name(file_name.clone());
+
+        let form = multipart::Form::new()
+            .part("name", multipart::Part::text(file_name.clone()))
+            .part("qqfile", file_part);
+
+        let res = self
+            .reqwest_client
+            .post(self.url(UPLOAD_FILE_URL).replace("{}", project_id))
+            .query(&[("folder_id", folder_id)])
+            .multipart(form)
+            .send_traced()
+            .await?;
+
+        ensure_success(res, format!("Failed to upload file {file_name}")).await?;
+
+        Ok(())
+    }
+
+    // Create folder with given name inside of specified folder in remote project.
+    pub async fn create_folder(
+        &self,
+        project_id: &str,
+        parent_folder_id: &str,
+        name: &str,
+    ) -> Result<Folder> {
+        let res = self
+            .reqwest_client
+            .post(self.url(CREATE_FOLDER_URL).replace("{}", project_id))
+            .json(&serde_json::json!({
+                "name": name,
+                "parent_folder_id": parent_folder_id,
+            }))
+            .send_traced()
+            .await?;
+
+        let res = ensure_success(res, format!("Failed to create folder {name}")).await?;
+
+        res.json().await.map_err(|e| {
+            anyhow!(format!(
+                "Failed to deserialize created folder with error: {e}."
+            ))
+        })
+    }
+
+    // Delete doc, file or folder with given id from remote project.
+    pub async fn delete_entity(
+        &self,
+        project_id: &str,
+        entity_type: EntityType,
+        entity_id: &str,
+    ) -> Result<()> {
+        let entity_kind = match entity_type {
+            EntityType::Doc => "doc",
+            EntityType::File => "file",
+            EntityType::Folder => "folder",
+        };
+
+        let res = self
+            .reqwest_client
+            .delete(
+                self.url(DELETE_ENTITY_URL)
+                    .replacen("{}", project_id, 1)
+                    .replacen("{}", entity_kind, 1)
+                    .replacen("{}", entity_id, 1),
+            )
+            .send_traced()
+            .await?;
+
+        ensure_success(res, format!("Failed to delete {entity_kind} {entity_id}")).await?;
+
+        Ok(())
+    }
+
+    // Compile specified project on Overleaf.
+    pub async fn request_compile(
+        &self,
+        project_id: &str,
+        options: &CompileOptions,
+    ) -> Result<CompileResult> {
+        let res = self
+            .reqwest_client
+            .post(self.url(COMPILE_PROJECT_URL).replace("{}", project_id))
+            .query(&[("auto_compile", "false")])
+            .json(&serde_json::json!({
+                "rootDoc_id": options.root_doc_id,
+                "draft": options.draft,
+                "check": "silent",
+                "incrementalCompilesEnabled": true,
+                "stopOnFirstError": options.stop_on_first_error,
+            }))
+            .send_traced()
+            .await?;
+
+        let res = ensure_success(res, format!("Failed to compile project {project_id}")).await?;
+
+        res.json().await.map_err(|e| {
+            anyhow!(format!(
+                "Failed to deserialize compile result with error: {e}."
+            ))
+        })
+    }
+
+    // Download output file produced by compilation.
+    pub async fn download_output_file(
+        &self,
+        compile_result: &CompileResult,
+        output_file: &OutputFile,
+    ) -> Result<Bytes> {
+        let mut query = Vec::new();
+
+        if let Some(compile_group) = &compile_result.compile_group {
+            query.push(("compileGroup", compile_group));
+        }
+
+        if let Some(clsi_server_id) = &compile_result.clsi_server_id {
+            query.push(("clsiserverid", clsi_server_id));
+        }
+
+        let res = self
+            .reqwest_client
+            .get(format!("{}{}", self.url(BASE_URL), output_file.url))
+            .query(&query)
+            .send_traced()
+            .await?;
+
+        let res = ensure_success(
+            res,
+            format!("Failed to download output file {}", output_file.path),
+        )
+        .await?;
+
+        res.bytes().await.context(format!(
+            "Error occured while downloading output file {}.",
+            output_file.path
+        ))
+    }
+
+    // Update settings of specified project.
+    pub async fn update_project_settings(
+        &self,
+        project_id: &str,
+        settings_update: &ProjectSettingsUpdate,
+    ) -> Result<()> {
+        let res = self
+            .reqwest_client
+            .post(self.url(PROJECT_SETTINGS_URL).replace("{}", project_id))
+            .json(settings_update)
+            .send_traced()
+            .await?;
+
+        ensure_success(
+            res,
+            format!("Failed to update settings of project {project_id}"),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    // Fetch page of project history updates, starting with the most recent ones older than
+    // `before` timestamp (in milliseconds).
+    pub async fn get_history_updates(
+        &self,
+        project_id: &str,
+        before: Option<i64>,
+        min_count: u32,
+    ) -> Result<HistoryUpdatesPage> {
+        let mut query = vec![("min_count", min_count.to_string())];
+
+        if let Some(before) = before {
+            query.push(("before", before.to_string()));
+        }
+
+        let res = self
+            .reqwest_client
+            .get(self.url(PROJECT_UPDATES_URL).replace("{}", project_id))
+            .query(&query)
+            .send_traced()
+            .await?;
+
+        let res = ensure_success(
+            res,
+            format!("Failed to fetch history of project {project_id}"),
+        )
+        .await?;
+
+        res.json().await.map_err(|e| {
+            anyhow!(format!(
+                "Failed to deserialize project history with error: {e}."
+            ))
+        })
+    }
+
+    // Fetch all history labels of specified project.
+    pub async fn get_labels(&self, project_id: &str) -> Result<Vec<Label>> {
+        let res = self
+            .reqwest_client
+            .get(self.url(PROJECT_LABELS_URL).replace("{}", project_id))
+            .send_traced()
+            .await?;
+
+        let res = ensure_success(
+            res,
+            format!("Failed to fetch history labels of project {project_id}"),
+        )
+        .await?;
+
+        res.json().await.map_err(|e| {
+            anyhow!(format!(
+                "Failed to deserialize history labels with error: {e}."
+            ))
+        })
+    }
+
+    // Get the most recent history version of specified project, if it has any history.
+    pub async fn get_latest_version(&self, project_id: &str) -> Result<Option<u64>> {
+        Ok(self
+            .get_history_updates(project_id, None, 1)
+            .await?
+            .updates
+            .first()
+            .map(|update| update.to_version))
+    }
+
+    // Create history label on given version of specified project.
+    pub async fn create_label(&self, project_id: &str, name: &str, version: u64) -> Result<Label> {
+        let res = self
+            .reqwest_client
+            .post(self.url(PROJECT_LABELS_URL).replace("{}", project_id))
+            .json(&serde_json::json!({
+                "comment": name,
+                "version": version,
+            }))
+            .send_traced()
+            .await?;
+
+        let res = ensure_success(res, format!("Failed to create history label {name}")).await?;
+
+        res.json().await.map_err(|e| {
+            anyhow!(format!(
+                "Failed to deserialize created history label with error: {e}."
+            ))
+        })
+    }
+
+    // Delete history label of specified project.
+    pub async fn delete_label(&self, project_id: &str, label_id: &str) -> Result<()> {
+        let res = self
+            .reqwest_client
+            .delete(
+                self.url(PROJECT_LABEL_URL)
+                    .replacen("{}", project_id, 1)
+                    .replacen("{}", label_id, 1),
+            )
+            .send_traced()
+            .await?;
+
+        ensure_success(res, format!("Failed to delete history label {label_id}")).await?;
+
+        Ok(())
+    }
+
+    // Download specified project as zip, as of given history version.
+    pub async fn download_project_version_zip(
+        &self,
+        project_id: &str,
+        version: u64,
+    ) -> Result<Bytes> {
+        let res = self
+            .reqwest_client
+            .get(
+                self.url(PROJECT_VERSION_ZIP_URL)
+                    .replacen("{}", project_id, 1)
+                    .replacen("{}", &version.to_string(), 1),
+            )
+            .send_traced()
+            .await?;
+
+        ensure_success(
+            res,
+            format!("Failed to download version {version} of project {project_id}"),
+        )
+        .await?
+        .bytes()
+        .await
+        .context(format!(
+            "Error occured while downloading version {version} of project {project_id} as zip."
+        ))
+    }
+
+    // Restore file in remote project to its state from given history version.
+    pub async fn restore_file(&self, project_id: &str, version: u64, pathname: &str) -> Result<()> {
+        let res = self
+            .reqwest_client
+            .post(self.url(RESTORE_FILE_URL).replace("{}", project_id))
+            .json(&serde_json::json!({
+                "version": version,
+                "pathname": pathname,
+            }))
+            .send_traced()
+            .await?;
+
+        ensure_success(
+            res,
+            format!("Failed to restore file {pathname} to version {version}"),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    // Fetch members of specified project together with their privileges.
+    pub async fn get_project_members(&self, project_id: &str) -> Result<Vec<Member>> {
+        let res = self
+            .reqwest_client
+            .get(self.url(PROJECT_MEMBERS_URL).replace("{}", project_id))
+            .send_traced()
+            .await?;
+
+        let res = ensure_success(
+            res,
+            format!("Failed to fetch members of project {project_id}"),
+        )
+        .await?;
+
+        res.json::<MembersList>()
+            .await
+            .map(|list| list.members)
+            .map_err(|e| {
+                anyhow!(format!(
+                    "Failed to deserialize members list with error: {e}."
+                ))
+            })
+    }
+
+    // Fetch pending invites to specified project.
+    pub async fn get_project_invites(&self, project_id: &str) -> Result<Vec<Invite>> {
+        let res = self
+            .reqwest_client
+            .get(self.url(PROJECT_INVITES_URL).replace("{}", project_id))
+            .send_traced()
+            .await?;
+
+        let res = ensure_success(
+            res,
+            format!("Failed to fetch invites to project {project_id}"),
+        )
+        .await?;
+
+        res.json::<InvitesList>()
+            .await
+            .map(|list| list.invites)
+            .map_err(|e| {
+                anyhow!(format!(
+                    "Failed to deserialize invites list with error: {e}."
+                ))
+            })
+    }
+
+    // Invite user to specified project with given privileges (readOnly, readAndWrite or review).
+    pub async fn invite_to_project(
+        &self,
+        project_id: &str,
+        email: &str,
+        privileges: &str,
+    ) -> Result<()> {
+        let res = self
+            .reqwest_client
+            .post(self.url(PROJECT_INVITE_URL).replace("{}", project_id))
+            .json(&serde_json::json!({
+                "email": email,
+                "privileges": privileges,
+            }))
+            .send_traced()
+            .await?;
+
+        ensure_success(res, format!("Failed to invite {email}")).await?;
+
+        Ok(())
+    }
+
+    // Revoke pending invite to specified project.
+    pub async fn revoke_invite(&self, project_id: &str, invite_id: &str) -> Result<()> {
+        let res = self
+            .reqwest_client
+            .delete(format!(
+                "{}/{invite_id}",
+                self.url(PROJECT_INVITE_URL).replace("{}", project_id)
+            ))
+            .send_traced()
+            .await?;
+
+        ensure_success(res, format!("Failed to revoke invite {invite_id}")).await?;
+
+        Ok(())
+    }
+
+    // Change privileges of project member.
+    pub async fn set_member_privileges(
+        &self,
+        project_id: &str,
+        user_id: &str,
+        privileges: &str,
+    ) -> Result<()> {
+        let res = self
+            .reqwest_client
+            .put(
+                self.url(PROJECT_MEMBER_URL)
+                    .replacen("{}", project_id, 1)
+                    .replacen("{}", user_id, 1),
+            )
+            .json(&serde_json::json!({ "privilegeLevel": privileges }))
+            .send_traced()
+            .await?;
+
+        ensure_success(
+            res,
+            format!("Failed to change privileges of user {user_id}"),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    // Remove member from specified project.
+    pub async fn remove_member(&self, project_id: &str, user_id: &str) -> Result<()> {
+        let res = self
+            .reqwest_client
+            .delete(
+                self.url(PROJECT_MEMBER_URL)
+                    .replacen("{}", project_id, 1)
+                    .replacen("{}", user_id, 1),
+            )
+            .send_traced()
+            .await?;
+
+        ensure_success(res, format!("Failed to remove user {user_id}")).await?;
+
+        Ok(())
+    }
+
+    // Transfer ownership of specified project to one of its members.
+    pub async fn transfer_ownership(&self, project_id: &str, user_id: &str) -> Result<()> {
+        let res = self
+            .reqwest_client
+            .post(self.url(TRANSFER_OWNERSHIP_URL).replace("{}", project_id))
+            .json(&serde_json::json!({ "user_id": user_id }))
+            .send_traced()
+            .await?;
+
+        ensure_success(
+            res,
+            format!("Failed to transfer ownership to user {user_id}"),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    // Turn link sharing of specified project on or off.
+    pub async fn set_link_sharing(&self, project_id: &str, enabled: bool) -> Result<()> {
+        let public_access_level = if enabled { "tokenBased" } else { "private" };
+
+        let res = self
+            .reqwest_client
+            .post(
+                self.url(PROJECT_ADMIN_SETTINGS_URL)
+                    .replace("{}", project_id),
+            )
+            .json(&serde_json::json!({ "publicAccessLevel": public_access_level }))
+            .send_traced()
+            .await?;
+
+        ensure_success(res, "Failed to change link sharing".to_owned()).await?;
+
+        Ok(())
+    }
+
+    // Fetch tokens of sharing links of specified project.
+    pub async fn get_share_tokens(&self, project_id: &str) -> Result<ShareTokens> {
+        let res = self
+            .reqwest_client
+            .get(self.url(PROJECT_TOKENS_URL).replace("{}", project_id))
+            .send_traced()
+            .await?;
+
+        let res = ensure_success(res, "Failed to fetch sharing links".to_owned()).await?;
+
+        res.json().await.map_err(|e| {
+            anyhow!(format!(
+                "Failed to deserialize sharing links with error: {e}."
+            ))
+        })
+    }
+
+    // Create new tag with given name.
+    pub async fn create_tag(&self, name: &str) -> Result<Tag> {
+        let res = self
+            .reqwest_client
+            .post(self.url(TAG_URL))
+            .json(&serde_json::json!({ "name": name }))
+            .send_traced()
+            .await?;
+
+        let res = ensure_success(res, format!("Failed to create tag {name}")).await?;
+
+        res.json().await.map_err(|e| {
+            anyhow!(format!(
+                "Failed to deserialize created tag with error: {e}."
+            ))
+        })
+    }
+
+    // Rename tag with given id.
+    pub async fn rename_tag(&self, tag_id: &str, new_name: &str) -> Result<()> {
+        let res = self
+            .reqwest_client
+            .post(format!("{}/{tag_id}/rename", self.url(TAG_URL)))
+            .json(&serde_json::json!({ "name": new_name }))
+            .send_traced()
+            .await?;
+
+        ensure_success(res, format!("Failed to rename tag {tag_id}")).await?;
+
+        Ok(())
+    }
+
+    // Delete tag with given id. Projects in the tag are not affected.
+    pub async fn delete_tag(&self, tag_id: &str) -> Result<()> {
+        let res = self
+            .reqwest_client
+            .delete(format!("{}/{tag_id}", self.url(TAG_URL)))
+            .send_traced()
+            .await?;
+
+        ensure_success(res, format!("Failed to delete tag {tag_id}")).await?;
+
+        Ok(())
+    }
+
+    // Add project to tag with given id.
+    pub async fn add_project_to_tag(&self, tag_id: &str, project_id: &str) -> Result<()> {
+        let res = self
+            .reqwest_client
+            .post(format!(
+                "{}/{tag_id}/project/{project_id}",
+                self.url(TAG_URL)
+            ))
+            .send_traced()
+            .await?;
+
+        ensure_success(
+            res,
+            format!("Failed to add project {project_id} to tag {tag_id}"),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    // Remove project from tag with given id.
+    pub async fn remove_project_from_tag(&self, tag_id: &str, project_id: &str) -> Result<()> {
+        let res = self
+            .reqwest_client
+            .delete(format!(
+                "{}/{tag_id}/project/{project_id}",
+                self.url(TAG_URL)
+            ))
+            .send_traced()
+            .await?;
+
+        ensure_success(
+            res,
+            format!("Failed to remove project {project_id} from tag {tag_id}"),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
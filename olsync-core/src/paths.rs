@@ -0,0 +1,86 @@
+use crate::{constants::OLSYNC_HOME_ENV, utils::path_to_str};
+
+use anyhow::{Context, Result};
+use log::info;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+fn olsync_home() -> Option<PathBuf> {
+    env::var_os(OLSYNC_HOME_ENV)
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+// Directory of global config, $XDG_CONFIG_HOME/olsync by default.
+pub fn config_dir() -> Result<PathBuf> {
+    if let Some(home) = olsync_home() {
+        return Ok(home);
+    }
+
+    dirs::config_dir()
+        .map(|dir| dir.join("olsync"))
+        .context("Failed to retrieve config directory.")
+}
+
+// Directory of sessions and other state kept between runs, $XDG_STATE_HOME/olsync by default, or
+// in the local data directory on systems without state directory.
+pub fn state_dir() -> Result<PathBuf> {
+    if let Some(home) = olsync_home() {
+        return Ok(home);
+    }
+
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join("olsync"))
+        .context("Failed to retrieve state directory.")
+}
+
+// Directory of daemon socket, $XDG_RUNTIME_DIR by default, or home directory on systems without
+// runtime directory.
+pub fn runtime_dir() -> Result<PathBuf> {
+    if let Some(home) = olsync_home() {
+        return Ok(home);
+    }
+
+    dirs::runtime_dir()
+        .or_else(dirs::home_dir)
+        .context("Failed to obtain directory for daemon socket.")
+}
+
+// Move file kept in home directory by older versions of olsync to its new path, unless there
+// already is a file there. Nothing is moved when OLSYNC_HOME is set, since it is meant to keep
+// olsync away from files in home directory.
+pub fn migrate_legacy_file(legacy_name: &str, new_path: &Path) -> Result<()> {
+    if olsync_home().is_some() || new_path.exists() {
+        return Ok(());
+    }
+
+    let Some(legacy_path) = dirs::home_dir().map(|home| home.join(legacy_name)) else {
+        return Ok(());
+    };
+
+    if !legacy_path.exists() {
+        return Ok(());
+    }
+
+    info!(
+        "Moving {} to {}.",
+        path_to_str(&legacy_path),
+        path_to_str(new_path)
+    );
+
+    if let Some(parent) = new_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Renaming fails when directories are on different filesystems, in which case file is copied.
+    fs::rename(&legacy_path, new_path)
+        .or_else(|_| fs::copy(&legacy_path, new_path).and_then(|_| fs::remove_file(&legacy_path)))
+        .context(format!(
+            "Failed to move {} to {}.",
+            path_to_str(&legacy_path),
+            path_to_str(new_path)
+        ))
+}
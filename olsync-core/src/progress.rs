@@ -0,0 +1,70 @@
+use serde::Serialize;
+
+// Progress of a long running operation. Library never writes to the terminal, instead it reports
+// progress to the consumer, which decides how to present it (e.g. as a spinner).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProgressEvent {
+    Started(String),
+    Finished(String),
+    Failed(String),
+}
+
+pub trait Progress: Send + Sync {
+    fn report(&self, event: ProgressEvent);
+}
+
+impl<F> Progress for F
+where
+    F: Fn(ProgressEvent) + Send + Sync,
+{
+    fn report(&self, event: ProgressEvent) {
+        self(event)
+    }
+}
+
+// Progress which ignores all events.
+pub struct NoProgress;
+
+impl Progress for NoProgress {
+    fn report(&self, _event: ProgressEvent) {}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncAction {
+    Updated,
+    Uploaded,
+    Pulled,
+    Moved,
+    Removed,
+    Skipped,
+    Failed,
+}
+
+// Change of a single file made while synchronizing, e.g. by watch or follow. Detail of moved
+// files is their new path.
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncEvent {
+    pub action: SyncAction,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl SyncEvent {
+    pub fn new(action: SyncAction, path: String) -> Self {
+        SyncEvent {
+            action,
+            path,
+            detail: None,
+        }
+    }
+
+    pub fn with_detail(action: SyncAction, path: String, detail: String) -> Self {
+        SyncEvent {
+            action,
+            path,
+            detail: Some(detail.replace('\n', " ")),
+        }
+    }
+}
@@ -0,0 +1,1250 @@
+use crate::{
+    api::OverleafApi,
+    backup::{apply_retention, create_backup},
+    config::Settings,
+    constants::{HISTORY_POLL_ATTEMPTS, HISTORY_POLL_INTERVAL_IN_MILLISECONDS},
+    error::OlsyncError,
+    overleaf_client::{
+        CompileOptions, CompileResult, EntityType, Folder, Label, OverleafClient, Project,
+        ProjectDetails, ProjectSettings,
+    },
+    progress::{Progress, ProgressEvent, SyncAction, SyncEvent},
+    utils::path_to_str,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use log::info;
+use std::collections::HashMap;
+use std::io::BufReader;
+use std::{env, path::Path, time::Duration};
+use std::{
+    fs::File,
+    io::{self, Cursor},
+};
+use std::{
+    fs::{self},
+    path::PathBuf,
+};
+
+// Returns root of olsync repository containing given path. It traverses directory hierarchy
+// starting from the path and going upwards.
+pub fn find_repo_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|dir| matches!(fs::exists(dir.join(".olsync")), Ok(true)))
+        .map(PathBuf::from)
+}
+
+// Returns .olsync directory in current repository. It traverses directory hierarchy starting from
+// currect directory and going upwards.
+pub fn get_olsync_directory() -> Option<PathBuf> {
+    find_repo_root(&env::current_dir().ok()?).map(|repo_root| repo_root.join(".olsync"))
+}
+
+// Check whether .olsync directory exists.
+pub fn is_olsync_repository() -> bool {
+    get_olsync_directory().is_some()
+}
+
+// Initialize new olsync repository of project in given directory and return its path.
+pub fn init_olsync_repository(project: &Project, repo_dir: &Path) -> Result<PathBuf> {
+    info!("Initializing empty olsync repository for project.");
+
+    if is_olsync_repository() {
+        bail!(OlsyncError::Conflict(
+            "This already is an olsync repository!".to_owned()
+        ));
+    }
+
+    let repo_dir = repo_dir.to_path_buf();
+
+    if fs::exists(repo_dir.clone())? {
+        bail!(OlsyncError::Conflict(format!(
+            "Directory {} already exists.",
+            path_to_str(repo_dir.as_path())
+        )))
+    }
+
+    fs::create_dir_all(repo_dir.join(".olsync"))?;
+    fs::write(
+        repo_dir.join(".olsync").join("projectinfo"),
+        serde_json::to_string(project)?,
+    )?;
+
+    Ok(repo_dir)
+}
+
+// Get project info of repository in given root directory.
+pub fn read_project_info(repo_root: &Path) -> Result<Project> {
+    File::open(repo_root.join(".olsync").join("projectinfo"))
+        .ok()
+        .and_then(|f| serde_json::from_reader(BufReader::new(f)).ok())
+        .ok_or(anyhow!(
+            "Failed to obtain project info from projectinfo file."
+        ))
+}
+
+// Get current repository project info.
+pub fn get_project_info() -> Result<Project> {
+    read_project_info(&get_repo_root()?)
+}
+
+// Overwrite current repository project info.
+pub fn save_project_info(project: &Project) -> Result<()> {
+    let project_info_path = get_olsync_directory()
+        .context("Failed to obtain .olsync directory.")?
+        .join("projectinfo");
+
+    fs::write(project_info_path, serde_json::to_string(project)?)
+        .context("Failed to save project info.")
+}
+
+// Save remote project settings in .olsync directory, so that local builds can mirror them.
+pub fn save_project_settings(settings: &ProjectSettings) -> Result<()> {
+    let settings_path = get_olsync_directory()
+        .context("Failed to obtain .olsync directory.")?
+        .join("settings");
+
+    fs::write(settings_path, serde_json::to_string_pretty(settings)?)
+        .context("Failed to save project settings.")
+}
+
+// Record time of the last clone or pull of repository in given root directory.
+pub fn save_last_pull_timestamp(repo_root: &Path) -> Result<()> {
+    fs::write(
+        repo_root.join(".olsync").join("lastpull"),
+        Utc::now().timestamp_millis().to_string(),
+    )
+    .context("Failed to save time of the last pull.")
+}
+
+// Get time of the last clone or pull of repository in given root directory in milliseconds.
+pub fn read_last_pull_timestamp(repo_root: &Path) -> Result<i64> {
+    fs::read_to_string(repo_root.join(".olsync").join("lastpull"))
+        .ok()
+        .and_then(|timestamp| timestamp.trim().parse().ok())
+        .ok_or(anyhow!("Failed to obtain time of the last pull."))
+}
+
+// Get time of the last clone or pull of current repository in milliseconds.
+pub fn get_last_pull_timestamp() -> Result<i64> {
+    get_repo_root()
+        .and_then(|repo_root| read_last_pull_timestamp(&repo_root))
+        .map_err(|_| anyhow!("Failed to obtain time of the last pull."))
+}
+
+// Save result of the last remote compilation in .olsync directory of repository in given root
+// directory.
+pub fn save_last_compile(repo_root: &Path, compile_result: &CompileResult) -> Result<()> {
+    fs::write(
+        repo_root.join(".olsync").join("lastcompile"),
+        serde_json::to_string(compile_result)?,
+    )
+    .context("Failed to save result of the last compilation.")
+}
+
+// Remember path relative to repository root where compile saved the PDF, so that it is never
+// pushed back, even when saved outside of the configured compile output.
+pub fn save_compile_output_path(repo_root: &Path, output: &str) -> Result<()> {
+    let output = output.trim_start_matches("./");
+    let mut output_paths = read_compile_output_paths(repo_root);
+
+    if output_paths.iter().any(|path| path == output) {
+        return Ok(());
+    }
+
+    output_paths.push(output.to_owned());
+
+    fs::write(
+        repo_root.join(".olsync").join("outputs"),
+        output_paths.join("\n") + "\n",
+    )
+    .context("Failed to save path of the compiled PDF.")
+}
+
+// Read paths relative to repository root where compile saved the PDF.
+pub fn read_compile_output_paths(repo_root: &Path) -> Vec<String> {
+    fs::read_to_string(repo_root.join(".olsync").join("outputs"))
+        .unwrap_or_default()
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+// Get result of the last remote compilation.
+pub fn get_last_compile() -> Result<CompileResult> {
+    get_olsync_directory()
+        .map(|dir| dir.join("lastcompile"))
+        .and_then(|last_compile_path| File::open(last_compile_path).ok())
+        .and_then(|f| serde_json::from_reader(BufReader::new(f)).ok())
+        .ok_or(anyhow!(
+            "Failed to obtain result of the last compilation. Compile the project first."
+        ))
+}
+
+// Get repository root directory.
+pub fn get_repo_root() -> Result<PathBuf> {
+    get_olsync_directory()
+        .and_then(|s| s.parent().map(PathBuf::from))
+        .ok_or_else(|| anyhow!("Failed to obtain project directory."))
+}
+
+// Copy everything in root directory except .olsync into a timestamp annotated backup
+// directory and return its path. Older backups beyond retention limits in settings are removed.
+pub fn backup_project(repo_root: &Path) -> Result<PathBuf> {
+    let bak_path = create_backup(repo_root)?;
+
+    apply_retention(repo_root, None)?;
+
+    Ok(bak_path)
+}
+
+// Create a timestamp annotated backup of local project.
+pub fn create_local_backup(repo_root: &Path, progress: &dyn Progress) -> Result<()> {
+    progress.report(ProgressEvent::Started(
+        "Creating backup of local project.".to_owned(),
+    ));
+
+    let backup_result = backup_project(repo_root);
+
+    if let Ok(bak_path) = backup_result {
+        progress.report(ProgressEvent::Finished(format!(
+            "Saved backup of local project in {}.",
+            path_to_str(bak_path.as_path())
+        )));
+        Ok(())
+    } else {
+        progress.report(ProgressEvent::Failed(
+            "Failed to create backup of local project.".to_owned(),
+        ));
+        bail!(backup_result.err().unwrap())
+    }
+}
+
+// Wipes everything in root directory except .olsync.
+pub fn wipe_project(repo_root: &Path) -> Result<()> {
+    info!("Wiping everything in repo root directory.");
+
+    let items_in_root = fs::read_dir(repo_root)?;
+
+    for item in items_in_root {
+        let path = item.unwrap().path();
+        let name = path.file_name().unwrap();
+
+        if name != ".olsync" {
+            fs_extra::remove_items(&[path.to_str().unwrap()])?;
+        }
+    }
+
+    Ok(())
+}
+
+// Download project from Overleaf in zip and save in target directory as {archive_name.zip}.
+// If archive_name is None, the archive will be extracted, replacing local state in target
+// directory. Nothing is changed unless the download is a valid zip archive.
+pub async fn download_project<A: OverleafApi>(
+    overleaf_client: &A,
+    project_id: &str,
+    target_dir: &Path,
+    archive_name: Option<String>,
+    progress: &dyn Progress,
+) -> Result<()> {
+    info!("Downloading project into {}.", path_to_str(target_dir));
+
+    progress.report(ProgressEvent::Started("Downloading project.".to_owned()));
+
+    let download_result = {
+        let download_closure = || async {
+            let archive: Vec<u8> = overleaf_client
+                .download_project_zip(project_id.to_owned())
+                .await?
+                .to_vec();
+
+            match archive_name {
+                Some(name) => {
+                    let file_name = format!("{}.zip", name);
+
+                    check_project_zip(&archive)?;
+
+                    fs::write(PathBuf::from(target_dir).join(&file_name), archive)
+                        .map(|()| format!("Saved project as {}.", file_name))
+                        .context("Failed to save downloaded project.".to_owned())
+                }
+                None => {
+                    check_project_zip(&archive)?;
+                    wipe_project(target_dir)?;
+
+                    extract_into_repository(archive, target_dir)
+                        .map(|()| "Downloaded and extracted project.".to_owned())
+                }
+            }
+        };
+
+        download_closure().await
+    };
+
+    if let Ok(message) = download_result {
+        progress.report(ProgressEvent::Finished(message));
+        Ok(())
+    } else {
+        progress.report(ProgressEvent::Failed(
+            "Failed to download and save project.".to_owned(),
+        ));
+        bail!(download_result.err().unwrap())
+    }
+}
+
+// Check that downloaded project is a zip archive, and not e.g. an error page, before it is saved
+// or extracted.
+pub fn check_project_zip(archive: &[u8]) -> Result<()> {
+    zip::ZipArchive::new(Cursor::new(archive))
+        .map(|_| ())
+        .context("Downloaded project is not a valid zip archive.")
+}
+
+// Extract zip archive of the project into repository root. If repository mirrors only a folder
+// of remote project, only files inside it are extracted.
+fn extract_into_repository(archive: Vec<u8>, repo_root: &Path) -> Result<()> {
+    extract_archive(archive, repo_root, &Settings::load(Some(repo_root))?)
+}
+
+// Extract zip archive of the project into target directory, mapping paths in remote project to
+// local ones by settings.
+fn extract_archive(archive: Vec<u8>, target_dir: &Path, settings: &Settings) -> Result<()> {
+    if settings.remote_dir.is_none() {
+        return zip_extract::extract(Cursor::new(archive), target_dir, true)
+            .context("Failed to extract downloaded project zip file.");
+    }
+
+    let mut zip = zip::ZipArchive::new(Cursor::new(archive))
+        .context("Failed to open downloaded project zip file.")?;
+
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index)?;
+
+        if entry.enclosed_name().is_none() {
+            continue;
+        }
+
+        let Some(path) = settings.local_path(entry.name().trim_end_matches('/')) else {
+            continue;
+        };
+
+        let target_path = target_dir.join(&path);
+
+        if entry.is_dir() {
+            fs::create_dir_all(&target_path)?;
+            continue;
+        }
+
+        if let Some(parent) = target_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        io::copy(&mut entry, &mut File::create(&target_path)?)
+            .context(format!("Failed to extract file {path}."))?;
+    }
+
+    Ok(())
+}
+
+// Extract zip archive of the project into repository root and record time of the pull.
+pub fn extract_project(archive: Vec<u8>, repo_root: &Path) -> Result<()> {
+    extract_into_repository(archive, repo_root)?;
+
+    save_last_pull_timestamp(repo_root)
+}
+
+// Extract specified files, given by paths relative to repository root, from zip archive into
+// target directory. Paths are mapped to ones in remote project by settings.
+fn extract_files(
+    archive: Vec<u8>,
+    target_dir: &Path,
+    files: &[&String],
+    settings: &Settings,
+) -> Result<()> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(archive))?;
+
+    for file_name in files {
+        let file_name = file_name.trim_start_matches("./");
+
+        let mut entry = zip
+            .by_name(&settings.remote_path(file_name))
+            .map_err(|_| anyhow!("File {file_name} does not exist in this version."))?;
+
+        let target_path = target_dir.join(file_name);
+
+        if let Some(parent) = target_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        io::copy(&mut entry, &mut File::create(&target_path)?)
+            .context(format!("Failed to extract file {file_name}."))?;
+    }
+
+    Ok(())
+}
+
+// Download project from Overleaf as of given history version and extract it in target
+// directory. If files are specified, only those files are extracted. Paths in remote project are
+// mapped to local ones by settings.
+pub async fn download_project_version(
+    overleaf_client: &OverleafClient,
+    project_id: &str,
+    version: u64,
+    target_dir: &Path,
+    files: &[&String],
+    settings: &Settings,
+    progress: &dyn Progress,
+) -> Result<()> {
+    info!(
+        "Downloading version {version} of project into {}.",
+        path_to_str(target_dir)
+    );
+
+    progress.report(ProgressEvent::Started(format!(
+        "Downloading version {version} of project."
+    )));
+
+    let download_result = {
+        let download_closure = || async {
+            let archive: Vec<u8> = overleaf_client
+                .download_project_version_zip(project_id, version)
+                .await?
+                .to_vec();
+
+            if files.is_empty() {
+                extract_archive(archive, target_dir, settings)
+                    .map(|()| format!("Downloaded and extracted version {version} of project."))
+            } else {
+                extract_files(archive, target_dir, files, settings).map(|()| {
+                    format!(
+                        "Downloaded {} files as of version {version} of project.",
+                        files.len()
+                    )
+                })
+            }
+        };
+
+        download_closure().await
+    };
+
+    if let Ok(message) = download_result {
+        progress.report(ProgressEvent::Finished(message));
+        Ok(())
+    } else {
+        progress.report(ProgressEvent::Failed(format!(
+            "Failed to download version {version} of project."
+        )));
+        bail!(download_result.err().unwrap())
+    }
+}
+
+// Fetch details of remote project, i.e. its file tree.
+pub fn fetch_project_details<A: OverleafApi>(
+    overleaf_client: &A,
+    project_id: &str,
+    progress: &dyn Progress,
+) -> Result<ProjectDetails> {
+    progress.report(ProgressEvent::Started(
+        "Fetching project details...".to_owned(),
+    ));
+
+    let details_result = overleaf_client.get_project_details(project_id);
+
+    if details_result.is_ok() {
+        progress.report(ProgressEvent::Finished(
+            "Fetched project details.".to_owned(),
+        ));
+    } else {
+        progress.report(ProgressEvent::Failed(
+            "Failed to fetch project details.".to_owned(),
+        ));
+    }
+
+    details_result
+}
+
+// Find id of remote folder with given path, creating missing folders along the way. Ids of
+// created folders are remembered, so that they are not created again for other files.
+async fn resolve_folder_id<A: OverleafApi>(
+    overleaf_client: &A,
+    project_id: &str,
+    root_folder: &Folder,
+    folder_path: &str,
+    created_folders: &mut HashMap<String, String>,
+) -> Result<String> {
+    let mut folder_id = root_folder.id.clone();
+    let mut prefix = String::new();
+
+    for name in folder_path.split('/').filter(|name| !name.is_empty()) {
+        if !prefix.is_empty() {
+            prefix.push('/');
+        }
+        prefix.push_str(name);
+
+        folder_id = if let Some(folder) = root_folder.find_folder(&prefix) {
+            folder.id.clone()
+        } else if let Some(id) = created_folders.get(&prefix) {
+            id.clone()
+        } else {
+            let folder = overleaf_client
+                .create_folder(project_id, &folder_id, name)
+                .await?;
+
+            created_folders.insert(prefix.clone(), folder.id.clone());
+            folder.id
+        };
+    }
+
+    Ok(folder_id)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PushOutcome {
+    UpdatedDoc,
+    UploadedFile,
+}
+
+impl PushOutcome {
+    pub fn description(&self) -> &'static str {
+        match self {
+            PushOutcome::UpdatedDoc => "Updated doc",
+            PushOutcome::UploadedFile => "Uploaded file",
+        }
+    }
+}
+
+impl SyncEvent {
+    // Describe outcome of pushing file with given path.
+    pub fn from_push(path: String, result: &Result<PushOutcome>) -> Self {
+        match result {
+            Ok(PushOutcome::UpdatedDoc) => SyncEvent::new(SyncAction::Updated, path),
+            Ok(PushOutcome::UploadedFile) => SyncEvent::new(SyncAction::Uploaded, path),
+            Err(err) => SyncEvent::with_detail(SyncAction::Failed, path, err.to_string()),
+        }
+    }
+}
+
+// Push single file given by path relative to repository root. Text files which already exist as
+// docs in remote project are updated in place, other files are uploaded into their folders.
+// Settings of the repository map the local path to the remote one.
+pub async fn push_file<A: OverleafApi>(
+    overleaf_client: &A,
+    project_id: &str,
+    root_folder: &Folder,
+    repo_root: &Path,
+    settings: &Settings,
+    path: &str,
+    created_folders: &mut HashMap<String, String>,
+) -> Result<PushOutcome> {
+    let local_path = path.trim_start_matches("./");
+    let file = fs::read(repo_root.join(local_path))?;
+
+    let path = &settings.remote_path(local_path);
+
+    let file = match (root_folder.find_doc(path), String::from_utf8(file)) {
+        (Some(doc), Ok(text)) => {
+            overleaf_client
+                .update_doc(project_id, &doc.id, &text)
+                .await?;
+            return Ok(PushOutcome::UpdatedDoc);
+        }
+        (_, file) => file.map_or_else(|e| e.into_bytes(), String::into_bytes),
+    };
+
+    let (folder_path, name) = path.rsplit_once('/').unwrap_or(("", path));
+
+    let folder_id = resolve_folder_id(
+        overleaf_client,
+        project_id,
+        root_folder,
+        folder_path,
+        created_folders,
+    )
+    .await?;
+
+    overleaf_client
+        .upload_file(project_id, &folder_id, name.to_owned(), file)
+        .await?;
+
+    Ok(PushOutcome::UploadedFile)
+}
+
+// Push list of files to Overleaf. Returns events describing how each file was pushed.
+pub async fn push_files<A: OverleafApi>(
+    overleaf_client: &A,
+    project_id: &str,
+    repo_root: &Path,
+    files: Vec<&String>,
+    progress: &dyn Progress,
+) -> Result<Vec<SyncEvent>> {
+    let project_details = fetch_project_details(overleaf_client, project_id, progress)?;
+
+    let root_folder = project_details
+        .root_folder
+        .first()
+        .context("Project details do not contain root folder.")?;
+
+    let settings = Settings::load(Some(repo_root))?;
+    let mut created_folders = HashMap::new();
+    let mut events = Vec::new();
+
+    for file_name in files {
+        progress.report(ProgressEvent::Started(format!(
+            "Pushing file {file_name}..."
+        )));
+
+        let push_result = push_file(
+            overleaf_client,
+            project_id,
+            root_folder,
+            repo_root,
+            &settings,
+            file_name,
+            &mut created_folders,
+        )
+        .await;
+
+        match push_result {
+            Ok(outcome) => {
+                progress.report(ProgressEvent::Finished(format!(
+                    "{} {file_name}.",
+                    outcome.description()
+                )));
+                events.push(SyncEvent::from_push(file_name.clone(), &Ok(outcome)));
+            }
+            Err(err) => {
+                progress.report(ProgressEvent::Failed(format!(
+                    "Failed to push file {file_name}."
+                )));
+                bail!(err)
+            }
+        }
+    }
+
+    Ok(events)
+}
+
+// Download doc or file with given id and save it at path relative to repository root.
+pub async fn pull_file<A: OverleafApi>(
+    overleaf_client: &A,
+    project_id: &str,
+    repo_root: &Path,
+    path: &str,
+    entity_type: EntityType,
+    entity_id: &str,
+) -> Result<()> {
+    let content = match entity_type {
+        EntityType::Doc => overleaf_client
+            .download_doc(project_id, entity_id)
+            .await?
+            .into_bytes(),
+        EntityType::File => overleaf_client
+            .download_file(project_id, entity_id)
+            .await?
+            .to_vec(),
+        EntityType::Folder => bail!("Folder {path} cannot be pulled as a file."),
+    };
+
+    let target_path = repo_root.join(path);
+
+    if let Some(parent) = target_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(&target_path, content).context(format!(
+        "Failed to save file {}.",
+        path_to_str(&target_path)
+    ))
+}
+
+// Download given paths of remote project and override their local state, leaving the rest of
+// the repository untouched. Paths pointing to folders are downloaded recursively. Returns events
+// of pulled files.
+pub async fn pull_files<A: OverleafApi>(
+    overleaf_client: &A,
+    project_id: &str,
+    repo_root: &Path,
+    paths: Vec<&String>,
+    progress: &dyn Progress,
+) -> Result<Vec<SyncEvent>> {
+    let project_details = fetch_project_details(overleaf_client, project_id, progress)?;
+
+    let root_folder = project_details
+        .root_folder
+        .first()
+        .context("Project details do not contain root folder.")?;
+
+    let settings = Settings::load(Some(repo_root))?;
+
+    let mut entities = Vec::new();
+    let mut events = Vec::new();
+
+    for path in paths {
+        entities.extend(
+            root_folder
+                .find_entities(&settings.remote_path(path))
+                .context(format!("File {path} does not exist in remote project."))?
+                .into_iter()
+                .filter_map(|(remote_path, entity_type, entity)| {
+                    settings
+                        .local_path(&remote_path)
+                        .map(|path| (path, entity_type, entity))
+                }),
+        );
+    }
+
+    for (path, entity_type, entity) in entities {
+        progress.report(ProgressEvent::Started(format!(
+            "Downloading file {path}..."
+        )));
+
+        let download_result = pull_file(
+            overleaf_client,
+            project_id,
+            repo_root,
+            &path,
+            entity_type,
+            &entity.id,
+        )
+        .await;
+
+        if let Ok(()) = download_result {
+            progress.report(ProgressEvent::Finished(format!("Downloaded file {path}.")));
+            events.push(SyncEvent::new(SyncAction::Pulled, path));
+        } else {
+            progress.report(ProgressEvent::Failed(format!(
+                "Failed to download file {path}."
+            )));
+            bail!(download_result.err().unwrap())
+        }
+    }
+
+    Ok(events)
+}
+
+// Replace whole local state of repository with the current state of remote project.
+pub async fn pull_project<A: OverleafApi>(
+    overleaf_client: &A,
+    project_id: &str,
+    repo_root: &Path,
+    progress: &dyn Progress,
+) -> Result<()> {
+    download_project(overleaf_client, project_id, repo_root, None, progress).await?;
+
+    save_last_pull_timestamp(repo_root)
+}
+
+// Options of compilation from settings. Main document is looked up in remote project, so that
+// its id can be sent instead of the one set in project settings.
+pub fn compile_options<A: OverleafApi>(
+    overleaf_client: &A,
+    project_id: &str,
+    settings: &Settings,
+) -> Result<CompileOptions> {
+    let root_doc_id = match &settings.main_document {
+        Some(main_document) => {
+            let project_details = overleaf_client.get_project_details(project_id)?;
+
+            let root_doc = project_details
+                .root_folder
+                .first()
+                .and_then(|root_folder| root_folder.find_doc(&settings.remote_path(main_document)))
+                .context(format!(
+                    "Main document {main_document} does not exist in remote project."
+                ))?;
+
+            Some(root_doc.id.clone())
+        }
+        None => None,
+    };
+
+    Ok(CompileOptions {
+        root_doc_id,
+        draft: settings.compile.draft,
+        stop_on_first_error: settings.compile.stop_on_first_error,
+    })
+}
+
+// Compile project of repository in given root directory on Overleaf, with options from its
+// settings. Compilation that finished with errors is not an error.
+pub async fn compile_project(
+    overleaf_client: &OverleafClient,
+    project_id: &str,
+    repo_root: &Path,
+    progress: &dyn Progress,
+) -> Result<CompileResult> {
+    progress.report(ProgressEvent::Started(
+        "Compiling project on Overleaf...".to_owned(),
+    ));
+
+    let compile_result = match compile_options(
+        overleaf_client,
+        project_id,
+        &Settings::load(Some(repo_root))?,
+    ) {
+        Ok(options) => overleaf_client.request_compile(project_id, &options).await,
+        Err(err) => Err(err),
+    };
+
+    match compile_result {
+        Ok(compile_result) => {
+            if compile_result.is_success() {
+                progress.report(ProgressEvent::Finished("Compiled project.".to_owned()));
+            } else {
+                progress.report(ProgressEvent::Failed(format!(
+                    "Compilation finished with status {}.",
+                    compile_result.status
+                )));
+            }
+
+            Ok(compile_result)
+        }
+        Err(err) => {
+            progress.report(ProgressEvent::Failed(
+                "Failed to compile project.".to_owned(),
+            ));
+            Err(err)
+        }
+    }
+}
+
+// Download output file of compilation (e.g. output.pdf) and save it at target path.
+pub async fn download_output_file(
+    overleaf_client: &OverleafClient,
+    compile_result: &CompileResult,
+    output_path: &str,
+    target_path: &Path,
+    progress: &dyn Progress,
+) -> Result<()> {
+    progress.report(ProgressEvent::Started(format!(
+        "Downloading {output_path}..."
+    )));
+
+    let download_result = {
+        let download_closure = || async {
+            let output_file = compile_result
+                .output_file(output_path)
+                .context(format!("Compilation did not produce {output_path}."))?;
+
+            let content = overleaf_client
+                .download_output_file(compile_result, output_file)
+                .await?;
+
+            fs::write(target_path, content).context(format!(
+                "Failed to save {output_path} in {}.",
+                path_to_str(target_path)
+            ))
+        };
+
+        download_closure().await
+    };
+
+    if let Ok(()) = download_result {
+        progress.report(ProgressEvent::Finished(format!(
+            "Saved {output_path} in {}.",
+            path_to_str(target_path)
+        )));
+        Ok(())
+    } else {
+        progress.report(ProgressEvent::Failed(format!(
+            "Failed to download {output_path}."
+        )));
+        bail!(download_result.err().unwrap())
+    }
+}
+
+// Fetch log of compilation.
+pub async fn fetch_compile_log(
+    overleaf_client: &OverleafClient,
+    compile_result: &CompileResult,
+) -> Result<String> {
+    let output_file = compile_result
+        .output_file("output.log")
+        .context("Compilation did not produce output.log.")?;
+
+    let log = overleaf_client
+        .download_output_file(compile_result, output_file)
+        .await?;
+
+    Ok(String::from_utf8_lossy(&log).into_owned())
+}
+
+// Create history label on the latest version of remote project. If `newer_than` is given,
+// waits until project history contains a version newer than it, since history of recent
+// changes becomes available with a delay. If no newer version appears, e.g. because pushed files
+// were unchanged, the current version is labeled.
+pub async fn create_label_on_latest_version(
+    overleaf_client: &OverleafClient,
+    project_id: &str,
+    name: &str,
+    newer_than: Option<u64>,
+    progress: &dyn Progress,
+) -> Result<Label> {
+    progress.report(ProgressEvent::Started(format!(
+        "Creating history label {name}..."
+    )));
+
+    let label_result = {
+        let label_closure = || async {
+            let mut attempts = 0;
+
+            let version = loop {
+                let latest_version = overleaf_client.get_latest_version(project_id).await?;
+                attempts += 1;
+
+                match (latest_version, newer_than) {
+                    (Some(version), Some(previous)) if version > previous => break version,
+                    (Some(version), None) => break version,
+                    (Some(version), Some(_)) if attempts >= HISTORY_POLL_ATTEMPTS => break version,
+                    _ if attempts >= HISTORY_POLL_ATTEMPTS => {
+                        bail!("Project history does not contain a version to label.")
+                    }
+                    _ => {
+                        tokio::time::sleep(Duration::from_millis(
+                            HISTORY_POLL_INTERVAL_IN_MILLISECONDS,
+                        ))
+                        .await
+                    }
+                }
+            };
+
+            overleaf_client
+                .create_label(project_id, name, version)
+                .await
+        };
+
+        label_closure().await
+    };
+
+    if let Ok(label) = label_result {
+        progress.report(ProgressEvent::Finished(format!(
+            "Created history label {name} on version {}.",
+            label.version
+        )));
+        Ok(label)
+    } else {
+        progress.report(ProgressEvent::Failed(format!(
+            "Failed to create history label {name}."
+        )));
+        bail!(label_result.err().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fake::{local_repository, FakeOverleaf},
+        progress::NoProgress,
+    };
+
+    #[tokio::test]
+    async fn push_updates_existing_doc_in_place() {
+        let overleaf = FakeOverleaf::new();
+        let project = overleaf.add_project("thesis");
+        let doc_id = overleaf
+            .add_doc(&project.id, "chapters/intro.tex", "old")
+            .unwrap();
+
+        let repo = local_repository(&project, &[("chapters/intro.tex", b"new")]);
+        let path = "chapters/intro.tex".to_owned();
+
+        push_files(
+            &overleaf,
+            &project.id,
+            repo.path(),
+            vec![&path],
+            &NoProgress,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            overleaf.entity_id(&project.id, "chapters/intro.tex"),
+            Some(doc_id)
+        );
+        assert_eq!(
+            overleaf.content(&project.id, "chapters/intro.tex"),
+            Some(b"new".to_vec())
+        );
+    }
+
+    #[tokio::test]
+    async fn push_uploads_new_files_into_created_folders() {
+        let overleaf = FakeOverleaf::new();
+        let project = overleaf.add_project("thesis");
+
+        let repo = local_repository(
+            &project,
+            &[
+                ("figures/plots/a.png", &[0xff, 0xd8, 0xff]),
+                ("figures/plots/b.tex", b"plot"),
+            ],
+        );
+        let paths = [
+            "./figures/plots/a.png".to_owned(),
+            "figures/plots/b.tex".to_owned(),
+        ];
+
+        push_files(
+            &overleaf,
+            &project.id,
+            repo.path(),
+            paths.iter().collect(),
+            &NoProgress,
+        )
+        .await
+        .unwrap();
+
+        let details = overleaf.get_project_details(&project.id).unwrap();
+        let plots = details.root_folder[0].find_folder("figures/plots").unwrap();
+
+        assert_eq!(plots.file_refs.len(), 1);
+        assert_eq!(plots.docs.len(), 1);
+        assert_eq!(
+            overleaf.content(&project.id, "figures/plots/a.png"),
+            Some(vec![0xff, 0xd8, 0xff])
+        );
+    }
+
+    #[tokio::test]
+    async fn push_replaces_existing_binary_file() {
+        let overleaf = FakeOverleaf::new();
+        let project = overleaf.add_project("thesis");
+        let file_id = overleaf
+            .add_file(&project.id, "logo.png", &[0x89, 0x50])
+            .unwrap();
+
+        let repo = local_repository(&project, &[("logo.png", &[0x89, 0x51, 0xff])]);
+        let path = "logo.png".to_owned();
+
+        push_files(
+            &overleaf,
+            &project.id,
+            repo.path(),
+            vec![&path],
+            &NoProgress,
+        )
+        .await
+        .unwrap();
+
+        assert_ne!(overleaf.entity_id(&project.id, "logo.png"), Some(file_id));
+        assert_eq!(
+            overleaf.content(&project.id, "logo.png"),
+            Some(vec![0x89, 0x51, 0xff])
+        );
+    }
+
+    #[tokio::test]
+    async fn push_fails_for_missing_local_file() {
+        let overleaf = FakeOverleaf::new();
+        let project = overleaf.add_project("thesis");
+
+        let repo = local_repository(&project, &[]);
+        let path = "missing.tex".to_owned();
+
+        let result = push_files(
+            &overleaf,
+            &project.id,
+            repo.path(),
+            vec![&path],
+            &NoProgress,
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(overleaf.content(&project.id, "missing.tex"), None);
+    }
+
+    #[tokio::test]
+    async fn pull_files_overrides_only_given_paths() {
+        let overleaf = FakeOverleaf::new();
+        let project = overleaf.add_project("thesis");
+        overleaf
+            .add_doc(&project.id, "main.tex", "remote main")
+            .unwrap();
+        overleaf
+            .add_doc(&project.id, "chapters/intro.tex", "remote intro")
+            .unwrap();
+        overleaf
+            .add_file(&project.id, "chapters/img/fig.png", &[0x01, 0x02])
+            .unwrap();
+
+        let repo = local_repository(
+            &project,
+            &[
+                ("main.tex", b"local main"),
+                ("chapters/intro.tex", b"local intro"),
+            ],
+        );
+        let path = "chapters".to_owned();
+
+        pull_files(
+            &overleaf,
+            &project.id,
+            repo.path(),
+            vec![&path],
+            &NoProgress,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(repo.path().join("main.tex")).unwrap(),
+            "local main"
+        );
+        assert_eq!(
+            fs::read_to_string(repo.path().join("chapters/intro.tex")).unwrap(),
+            "remote intro"
+        );
+        assert_eq!(
+            fs::read(repo.path().join("chapters/img/fig.png")).unwrap(),
+            vec![0x01, 0x02]
+        );
+    }
+
+    #[tokio::test]
+    async fn pull_files_fails_for_unknown_path() {
+        let overleaf = FakeOverleaf::new();
+        let project = overleaf.add_project("thesis");
+
+        let repo = local_repository(&project, &[]);
+        let path = "missing.tex".to_owned();
+
+        let result = pull_files(
+            &overleaf,
+            &project.id,
+            repo.path(),
+            vec![&path],
+            &NoProgress,
+        )
+        .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn pull_project_replaces_local_state() {
+        let overleaf = FakeOverleaf::new();
+        let project = overleaf.add_project("thesis");
+        overleaf
+            .add_doc(&project.id, "main.tex", "remote main")
+            .unwrap();
+        overleaf
+            .add_doc(&project.id, "chapters/intro.tex", "remote intro")
+            .unwrap();
+
+        let repo = local_repository(
+            &project,
+            &[("main.tex", b"local main"), ("notes.txt", b"local only")],
+        );
+
+        pull_project(&overleaf, &project.id, repo.path(), &NoProgress)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(repo.path().join("main.tex")).unwrap(),
+            "remote main"
+        );
+        assert_eq!(
+            fs::read_to_string(repo.path().join("chapters/intro.tex")).unwrap(),
+            "remote intro"
+        );
+        assert!(!repo.path().join("notes.txt").exists());
+        assert_eq!(read_project_info(repo.path()).unwrap().id, project.id);
+        assert!(repo.path().join(".olsync").join("lastpull").exists());
+    }
+
+    #[tokio::test]
+    async fn backup_keeps_copy_of_local_state() {
+        let overleaf = FakeOverleaf::new();
+        let project = overleaf.add_project("thesis");
+
+        let repo = local_repository(&project, &[("main.tex", b"local main")]);
+
+        let bak_path = backup_project(repo.path()).unwrap();
+
+        assert!(bak_path.starts_with(repo.path().join(".olsync")));
+        assert_eq!(
+            fs::read_to_string(bak_path.join("main.tex")).unwrap(),
+            "local main"
+        );
+    }
+
+    #[tokio::test]
+    async fn only_zip_archives_pass_project_check() {
+        let overleaf = FakeOverleaf::new();
+        let project = overleaf.add_project("thesis");
+        overleaf.add_doc(&project.id, "main.tex", "main").unwrap();
+
+        let archive = overleaf
+            .download_project_zip(project.id.clone())
+            .await
+            .unwrap();
+
+        assert!(check_project_zip(&archive).is_ok());
+        assert!(check_project_zip(b"<html>Log in to Overleaf</html>").is_err());
+    }
+
+    #[tokio::test]
+    async fn pull_project_keeps_local_state_if_download_is_not_zip() {
+        let overleaf = FakeOverleaf::new();
+        let project = overleaf.add_project("thesis");
+        overleaf.serve_invalid_zips();
+
+        let repo = local_repository(&project, &[("main.tex", b"local main")]);
+
+        let result = pull_project(&overleaf, &project.id, repo.path(), &NoProgress).await;
+
+        assert!(result.is_err());
+        assert_eq!(
+            fs::read_to_string(repo.path().join("main.tex")).unwrap(),
+            "local main"
+        );
+    }
+
+    #[tokio::test]
+    async fn extracted_files_are_mapped_into_remote_dir() {
+        let overleaf = FakeOverleaf::new();
+        let project = overleaf.add_project("course");
+        overleaf
+            .add_doc(&project.id, "thesis/main.tex", "thesis main")
+            .unwrap();
+        overleaf
+            .add_doc(&project.id, "slides/main.tex", "slides main")
+            .unwrap();
+
+        let archive = overleaf
+            .download_project_zip(project.id.clone())
+            .await
+            .unwrap()
+            .to_vec();
+
+        let settings = Settings {
+            remote_dir: Some("thesis".to_owned()),
+            ..Settings::default()
+        };
+
+        let whole = tempfile::tempdir().unwrap();
+        extract_archive(archive.clone(), whole.path(), &settings).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(whole.path().join("main.tex")).unwrap(),
+            "thesis main"
+        );
+        assert!(!whole.path().join("slides").exists());
+
+        let single = tempfile::tempdir().unwrap();
+        let file = "./main.tex".to_owned();
+        extract_files(archive, single.path(), &[&file], &settings).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(single.path().join("main.tex")).unwrap(),
+            "thesis main"
+        );
+    }
+}
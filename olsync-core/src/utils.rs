@@ -0,0 +1,18 @@
+use std::{env, path::Path};
+
+use crate::constants::{BASE_URL, BASE_URL_ENV};
+
+pub fn path_to_str(path: &Path) -> &str {
+    path.to_str().unwrap_or("INVALID PATH")
+}
+
+// Resolve URL of Overleaf endpoint on instance with given base URL, which can be overridden in
+// environment.
+pub fn overleaf_url(instance_url: &str, url: &str) -> String {
+    let base_url = match env::var(BASE_URL_ENV) {
+        Ok(base_url) if !base_url.is_empty() => base_url,
+        _ => instance_url.to_owned(),
+    };
+
+    url.replacen(BASE_URL, base_url.trim_end_matches('/'), 1)
+}
@@ -0,0 +1,254 @@
+use crate::{
+    api::OverleafApi,
+    config::Settings,
+    progress::SyncEvent,
+    repository::{push_file, read_compile_output_paths, PushOutcome},
+    utils::path_to_str,
+};
+
+use anyhow::{anyhow, Context, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use log::{error, info};
+use notify::RecursiveMode;
+use notify_debouncer_mini::new_debouncer;
+use std::{collections::HashMap, fs, path::Path, time::Duration};
+use tokio::sync::mpsc;
+
+const IGNORE_FILE_NAME: &str = ".olsyncignore";
+
+// Patterns ignored in every repository: olsync metadata, version control, temporary files of
+// editors (4913 is created by Vim to test write permissions), output files downloaded by compile
+// and output, and auxiliary files of local LaTeX builds.
+const DEFAULT_IGNORE_PATTERNS: [&str; 17] = [
+    ".olsync/",
+    ".git/",
+    "*.swp",
+    "*.swx",
+    "*~",
+    ".#*",
+    "4913",
+    IGNORE_FILE_NAME,
+    "output.*",
+    "*.aux",
+    "*.log",
+    "*.synctex.gz",
+    "*.fls",
+    "*.fdb_latexmk",
+    "*.blg",
+    "*.toc",
+    "*.out",
+];
+
+// Rules deciding which files in repository are never pushed.
+pub struct IgnoreRules {
+    globs: GlobSet,
+}
+
+impl IgnoreRules {
+    // Read ignore rules from .olsyncignore in repository root and from settings, on top of the
+    // default ones. PDF saved by compile is ignored also when saved under another path, including
+    // paths given once with `compile -o`.
+    pub fn load(repo_root: &Path) -> Result<Self> {
+        let settings = Settings::load(Some(repo_root))?;
+        let custom_patterns =
+            fs::read_to_string(repo_root.join(IGNORE_FILE_NAME)).unwrap_or_default();
+        let compile_outputs: Vec<_> = [settings.compile.output.trim_start_matches("./").to_owned()]
+            .into_iter()
+            .chain(read_compile_output_paths(repo_root))
+            .map(|output| format!("/{output}"))
+            .collect();
+
+        let patterns = DEFAULT_IGNORE_PATTERNS
+            .into_iter()
+            .chain(
+                custom_patterns
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#')),
+            )
+            .chain(settings.ignore.iter().map(String::as_str))
+            .chain(compile_outputs.iter().map(String::as_str));
+
+        let mut builder = GlobSetBuilder::new();
+
+        for pattern in patterns {
+            for glob in expand_pattern(pattern) {
+                builder.add(
+                    GlobBuilder::new(&glob)
+                        .literal_separator(true)
+                        .build()
+                        .context(format!("Invalid ignore pattern {pattern}."))?,
+                );
+            }
+        }
+
+        Ok(IgnoreRules {
+            globs: builder.build()?,
+        })
+    }
+
+    // Check whether path relative to repository root is ignored.
+    pub fn is_ignored(&self, path: &str) -> bool {
+        self.globs.is_match(path)
+    }
+}
+
+// Translate .gitignore-like pattern into globs matched against paths relative to repository root.
+// Patterns without slash match at any depth, patterns starting with slash only in the root and
+// patterns ending with slash only match directories, together with everything inside.
+fn expand_pattern(pattern: &str) -> Vec<String> {
+    let (pattern, directory_only) = match pattern.strip_suffix('/') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+
+    let pattern = match pattern.strip_prefix('/') {
+        Some(anchored) => anchored.to_owned(),
+        None if pattern.contains('/') => pattern.to_owned(),
+        None => format!("**/{pattern}"),
+    };
+
+    if directory_only {
+        vec![format!("{pattern}/**")]
+    } else {
+        vec![format!("{pattern}/**"), pattern]
+    }
+}
+
+// Watch repository for changes and push changed files to Overleaf, until interrupted. Changes
+// are collected until no new ones come for the debounce duration, so that a burst of writes
+// results in a single push. Every pushed file is reported to `on_event`.
+pub async fn watch_repository<A: OverleafApi>(
+    overleaf_client: &A,
+    project_id: &str,
+    repo_root: &Path,
+    debounce: Duration,
+    on_event: &(dyn Fn(SyncEvent) + Send + Sync),
+) -> Result<()> {
+    let ignore_rules = IgnoreRules::load(repo_root)?;
+    let settings = Settings::load(Some(repo_root))?;
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    let mut debouncer = new_debouncer(debounce, move |result| {
+        let _ = tx.send(result);
+    })
+    .context("Failed to start watching files.")?;
+
+    debouncer
+        .watcher()
+        .watch(repo_root, RecursiveMode::Recursive)
+        .context(format!("Failed to watch {}.", path_to_str(repo_root)))?;
+
+    let mut project_details = overleaf_client.get_project_details(project_id)?;
+    let mut created_folders = HashMap::new();
+
+    info!(
+        "Watching {} for changes. Press Ctrl+C to stop.",
+        path_to_str(repo_root)
+    );
+
+    while let Some(result) = rx.recv().await {
+        let events = match result {
+            Ok(events) => events,
+            Err(err) => {
+                error!("Failed to watch files: {err}");
+                continue;
+            }
+        };
+
+        let mut paths: Vec<String> = events
+            .into_iter()
+            .filter(|event| event.path.is_file())
+            .filter_map(|event| {
+                event
+                    .path
+                    .strip_prefix(repo_root)
+                    .ok()
+                    .and_then(Path::to_str)
+                    .map(str::to_owned)
+            })
+            .filter(|path| !ignore_rules.is_ignored(path))
+            .collect();
+
+        paths.sort();
+        paths.dedup();
+
+        let mut uploaded = false;
+
+        for path in paths {
+            let result = match project_details.root_folder.first() {
+                Some(root_folder) => {
+                    push_file(
+                        overleaf_client,
+                        project_id,
+                        root_folder,
+                        repo_root,
+                        &settings,
+                        &path,
+                        &mut created_folders,
+                    )
+                    .await
+                }
+                None => Err(anyhow!("Project details do not contain root folder.")),
+            };
+
+            uploaded |= matches!(result, Ok(PushOutcome::UploadedFile));
+
+            on_event(SyncEvent::from_push(path, &result));
+        }
+
+        // Uploaded files become new entities in remote project, so details have to be refreshed
+        // for their later changes to be pushed as doc updates.
+        if uploaded {
+            match overleaf_client.get_project_details(project_id) {
+                Ok(details) => {
+                    project_details = details;
+                    created_folders.clear();
+                }
+                Err(err) => error!("Failed to refresh project details: {err}"),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::save_compile_output_path;
+    use tempfile::tempdir;
+
+    #[test]
+    fn compile_artifacts_are_ignored_by_default() -> Result<()> {
+        let dir = tempdir()?;
+        fs::create_dir_all(dir.path().join(".olsync"))?;
+
+        save_compile_output_path(dir.path(), "./other.pdf")?;
+
+        let ignore_rules = IgnoreRules::load(dir.path())?;
+
+        for path in [
+            "output.pdf",
+            "output.log",
+            "output.synctex.gz",
+            "build/output.bbl",
+            "main.aux",
+            "chapters/intro.log",
+            "main.synctex.gz",
+            "other.pdf",
+        ] {
+            assert!(ignore_rules.is_ignored(path), "{path} should be ignored");
+        }
+
+        for path in ["main.tex", "figures/plot.pdf", "references.bib", "main.bbl"] {
+            assert!(
+                !ignore_rules.is_ignored(path),
+                "{path} should not be ignored"
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,427 @@
+pub mod socketio;
+pub mod store;
+
+use anyhow::{anyhow, Context, Result};
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    body::Incoming,
+    header::{CONTENT_TYPE, COOKIE, LOCATION, SET_COOKIE},
+    server::conn::http1,
+    service::service_fn,
+    Method, Request, Response, StatusCode,
+};
+use hyper_util::rt::TokioIo;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{mpsc, Arc},
+    thread,
+};
+use tokio::{net::TcpListener, sync::oneshot};
+
+use crate::{
+    socketio::SocketIo,
+    store::{EntityKind, Store},
+};
+
+pub const MOCK_EMAIL: &str = "mock@example.com";
+pub const MOCK_CSRF_TOKEN: &str = "mock-csrf-token";
+
+const SESSION_COOKIE_NAME: &str = "overleaf_session2";
+
+struct State {
+    store: Store,
+    socket_io: SocketIo,
+}
+
+type HttpResponse = Response<Full<Bytes>>;
+
+fn response(status: StatusCode, content_type: &str, body: impl Into<Bytes>) -> HttpResponse {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, content_type)
+        .body(Full::new(body.into()))
+        .unwrap()
+}
+
+fn json_response(value: Value) -> HttpResponse {
+    response(StatusCode::OK, "application/json", value.to_string())
+}
+
+fn text_response(text: String) -> HttpResponse {
+    response(StatusCode::OK, "text/plain; charset=UTF-8", text)
+}
+
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn html_page(metas: &[(&str, String)], body: &str) -> HttpResponse {
+    let metas: String = metas
+        .iter()
+        .map(|(name, content)| {
+            format!(
+                "<meta name=\"{name}\" content=\"{}\">\n",
+                escape_attribute(content)
+            )
+        })
+        .collect();
+
+    response(
+        StatusCode::OK,
+        "text/html; charset=UTF-8",
+        format!("<!DOCTYPE html>\n<html><head>\n{metas}</head><body>{body}</body></html>\n"),
+    )
+}
+
+fn query_params(request: &Request<Incoming>) -> HashMap<String, String> {
+    request
+        .uri()
+        .query()
+        .unwrap_or("")
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        .collect()
+}
+
+fn has_session(request: &Request<Incoming>) -> bool {
+    request
+        .headers()
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .any(|value| value.contains(&format!("{SESSION_COOKIE_NAME}=")))
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+struct Part {
+    file_name: Option<String>,
+    content: Vec<u8>,
+}
+
+// Parse multipart/form-data body into parts by field name.
+fn parse_multipart(content_type: &str, body: &[u8]) -> Result<HashMap<String, Part>> {
+    let boundary = content_type
+        .split("boundary=")
+        .nth(1)
+        .context("Multipart boundary not found.")?
+        .trim_matches('"');
+    let delimiter = format!("--{boundary}").into_bytes();
+
+    let mut parts = HashMap::new();
+    let mut rest = body;
+
+    while let Some(start) = find(rest, &delimiter) {
+        rest = &rest[start + delimiter.len()..];
+
+        if rest.starts_with(b"--") {
+            break;
+        }
+
+        let rest_of_part = rest.strip_prefix(b"\r\n").unwrap_or(rest);
+        let headers_end = find(rest_of_part, b"\r\n\r\n").context("Invalid multipart part.")?;
+        let headers = String::from_utf8_lossy(&rest_of_part[..headers_end]).into_owned();
+        let content_start = &rest_of_part[headers_end + 4..];
+        let content_end = find(content_start, &delimiter).context("Unterminated multipart.")?;
+        let content = content_start[..content_end]
+            .strip_suffix(b"\r\n")
+            .unwrap_or(&content_start[..content_end]);
+
+        let attribute = |name: &str| {
+            headers
+                .split(&format!("{name}=\""))
+                .nth(1)
+                .and_then(|value| value.split('"').next())
+                .map(str::to_owned)
+        };
+
+        if let Some(name) = attribute(" name") {
+            parts.insert(
+                name,
+                Part {
+                    file_name: attribute("filename"),
+                    content: content.to_vec(),
+                },
+            );
+        }
+
+        rest = content_start;
+    }
+
+    Ok(parts)
+}
+
+async fn handle(state: Arc<State>, request: Request<Incoming>) -> Result<HttpResponse> {
+    let method = request.method().clone();
+    let path = request.uri().path().to_owned();
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let query = query_params(&request);
+
+    let authenticated = has_session(&request);
+    let requires_session = segments.first() == Some(&"project");
+
+    if requires_session && !authenticated {
+        return Ok(Response::builder()
+            .status(StatusCode::FOUND)
+            .header(LOCATION, "/login")
+            .body(Full::new(Bytes::new()))
+            .unwrap());
+    }
+
+    let content_type = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("")
+        .to_owned();
+
+    let body = request.into_body().collect().await?.to_bytes();
+    let store = &state.store;
+
+    Ok(match (&method, segments.as_slice()) {
+        (&Method::GET, ["login"]) => html_page(
+            &[
+                ("ol-csrfToken", MOCK_CSRF_TOKEN.to_owned()),
+                ("ol-usersEmail", MOCK_EMAIL.to_owned()),
+            ],
+            "<button id=\"new-project-button-sidebar\">New project</button>",
+        ),
+        (&Method::GET, ["project"]) => {
+            let projects: Vec<Value> = store
+                .projects()?
+                .into_iter()
+                .map(|(id, name)| json!({ "id": id, "name": name }))
+                .collect();
+
+            html_page(
+                &[
+                    ("ol-usersEmail", MOCK_EMAIL.to_owned()),
+                    ("ol-csrfToken", MOCK_CSRF_TOKEN.to_owned()),
+                    (
+                        "ol-prefetchedProjectsBlob",
+                        json!({ "totalSize": projects.len(), "projects": projects }).to_string(),
+                    ),
+                    ("ol-tags", "[]".to_owned()),
+                ],
+                "",
+            )
+        }
+        (&Method::GET, ["project", project_id, "download", "zip"]) => {
+            response(StatusCode::OK, "application/zip", store.zip(project_id)?)
+        }
+        (&Method::GET, ["project", project_id, "doc", doc_id, "download"]) => {
+            let path = store.find_path(project_id, doc_id, EntityKind::Doc)?;
+            response(
+                StatusCode::OK,
+                "text/plain; charset=UTF-8",
+                store.read(project_id, &path)?,
+            )
+        }
+        (&Method::GET, ["project", project_id, "file", file_id]) => {
+            let path = store.find_path(project_id, file_id, EntityKind::File)?;
+            response(
+                StatusCode::OK,
+                "application/octet-stream",
+                store.read(project_id, &path)?,
+            )
+        }
+        (&Method::POST, ["project", project_id, "upload"]) => {
+            let folder_id = query.get("folder_id").context("Missing folder_id.")?;
+            let parts = parse_multipart(&content_type, &body)?;
+
+            let file = parts.get("qqfile").context("Missing qqfile part.")?;
+            let name = match parts.get("name") {
+                Some(name) => String::from_utf8(name.content.clone())?,
+                None => file.file_name.clone().context("Missing file name.")?,
+            };
+
+            let (entity_id, kind) = store.upload(project_id, folder_id, &name, &file.content)?;
+
+            json_response(json!({
+                "success": true,
+                "entity_id": entity_id,
+                "entity_type": if kind == EntityKind::Doc { "doc" } else { "file" },
+            }))
+        }
+        (&Method::POST, ["project", project_id, "folder"]) => {
+            let request: Value = serde_json::from_slice(&body)?;
+            let name = request["name"].as_str().context("Missing folder name.")?;
+            let parent_folder_id = request["parent_folder_id"]
+                .as_str()
+                .context("Missing parent folder id.")?;
+
+            let folder_id = store.create_folder(project_id, parent_folder_id, name)?;
+
+            json_response(store.folder(project_id, &folder_id)?)
+        }
+        (
+            &Method::DELETE,
+            ["project", project_id, kind @ ("doc" | "file" | "folder"), entity_id],
+        ) => {
+            let kind = match *kind {
+                "folder" => EntityKind::Folder,
+                "doc" => EntityKind::Doc,
+                _ => EntityKind::File,
+            };
+
+            store.delete(project_id, entity_id, kind)?;
+
+            response(StatusCode::NO_CONTENT, "text/plain", Vec::new())
+        }
+        (&Method::GET, ["socket.io", "socket.io.js"]) => Response::builder()
+            .status(StatusCode::OK)
+            .header(SET_COOKIE, "GCLB=mock-gclb; Path=/")
+            .header(CONTENT_TYPE, "application/javascript")
+            .body(Full::new(Bytes::new()))
+            .unwrap(),
+        (&Method::GET, ["socket.io", "1"]) => {
+            let project_id = query.get("projectId").context("Missing projectId.")?;
+            store.project_name(project_id)?;
+
+            text_response(state.socket_io.handshake(project_id))
+        }
+        (&Method::GET, ["socket.io", "1", "xhr-polling", session_id]) => {
+            if query.contains_key("disconnect") {
+                state.socket_io.disconnect(session_id);
+                text_response(String::new())
+            } else {
+                text_response(state.socket_io.poll(store, session_id).await?)
+            }
+        }
+        (&Method::POST, ["socket.io", "1", "xhr-polling", session_id]) => {
+            state
+                .socket_io
+                .receive(store, session_id, &String::from_utf8(body.to_vec())?)?;
+
+            text_response("1".to_owned())
+        }
+        _ => return Err(anyhow!("Not found: {method} {path}")),
+    })
+}
+
+async fn serve(state: Arc<State>, request: Request<Incoming>) -> Result<HttpResponse, Infallible> {
+    let not_found = request.uri().path().to_owned();
+
+    Ok(handle(state, request).await.unwrap_or_else(|err| {
+        let status = if err.to_string().starts_with("Not found") {
+            StatusCode::NOT_FOUND
+        } else {
+            StatusCode::BAD_REQUEST
+        };
+
+        response(status, "text/plain", format!("{not_found}: {err}"))
+    }))
+}
+
+async fn run(listener: TcpListener, state: Arc<State>, mut shutdown: oneshot::Receiver<()>) {
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(_) => continue,
+            },
+            _ = &mut shutdown => break,
+        };
+
+        let state = state.clone();
+
+        tokio::spawn(async move {
+            let service = service_fn(move |request| serve(state.clone(), request));
+            let _ = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await;
+        });
+    }
+}
+
+// Mock of Overleaf serving projects stored in root directory, one subdirectory per project. It
+// runs on its own thread until dropped.
+pub struct MockOverleaf {
+    address: SocketAddr,
+    shutdown: Option<oneshot::Sender<()>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl MockOverleaf {
+    // Start server on given address. Port 0 picks a free port.
+    pub fn start(root: PathBuf, address: SocketAddr) -> Result<Self> {
+        let (address_tx, address_rx) = mpsc::channel();
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+
+        let thread = thread::spawn(move || {
+            let runtime = match tokio::runtime::Runtime::new() {
+                Ok(runtime) => runtime,
+                Err(err) => {
+                    let _ = address_tx.send(Err(anyhow!(err)));
+                    return;
+                }
+            };
+
+            runtime.block_on(async move {
+                let listener = match TcpListener::bind(address).await {
+                    Ok(listener) => listener,
+                    Err(err) => {
+                        let _ = address_tx.send(Err(anyhow!(err)));
+                        return;
+                    }
+                };
+
+                let _ = address_tx.send(listener.local_addr().map_err(|err| anyhow!(err)));
+
+                let state = Arc::new(State {
+                    store: Store::new(root),
+                    socket_io: SocketIo::default(),
+                });
+
+                run(listener, state, shutdown_rx).await;
+            });
+        });
+
+        let address = address_rx
+            .recv()
+            .context("Mock server thread stopped before binding.")??;
+
+        Ok(MockOverleaf {
+            address,
+            shutdown: Some(shutdown_tx),
+            thread: Some(thread),
+        })
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    // Block until the server stops.
+    pub fn wait(mut self) {
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for MockOverleaf {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
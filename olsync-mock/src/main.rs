@@ -0,0 +1,28 @@
+use anyhow::{Context, Result};
+use olsync_mock::MockOverleaf;
+use std::{env, net::SocketAddr, path::PathBuf};
+
+const USAGE: &str = "Usage: olsync-mock <projects directory> [address, default 127.0.0.1:8080]";
+
+fn main() -> Result<()> {
+    let mut args = env::args().skip(1);
+
+    let root = PathBuf::from(args.next().context(USAGE)?);
+    let address: SocketAddr = args
+        .next()
+        .unwrap_or("127.0.0.1:8080".to_owned())
+        .parse()
+        .context(USAGE)?;
+
+    let server = MockOverleaf::start(root, address)?;
+
+    println!("Serving mock Overleaf on {}", server.base_url());
+    println!(
+        "Use it with: OLSYNC_BASE_URL={} olsync ...",
+        server.base_url()
+    );
+
+    server.wait();
+
+    Ok(())
+}
@@ -0,0 +1,273 @@
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use crate::store::{EntityKind, Store};
+
+// Separator of packets sent in a single xhr-polling response.
+const FRAME_BOUNDARY: char = '\u{fffd}';
+
+// How long a poll waits for packets before responding with noop.
+const POLL_WAIT: Duration = Duration::from_millis(200);
+
+struct Session {
+    project_id: String,
+    queue: Vec<String>,
+    polls: u64,
+    // Whether client sent any event. Until then, joinProjectResponse is repeated in every poll,
+    // since client may start listening for it only after the connection is established.
+    active: bool,
+}
+
+// Server side of socket.io 0.9 protocol over xhr-polling, the subset used by
+// olsync-rs-socketio-client: joining project, joining docs and applying updates to them.
+#[derive(Default)]
+pub struct SocketIo {
+    sessions: Mutex<HashMap<String, Session>>,
+    versions: Mutex<HashMap<String, u64>>,
+    next_session_id: AtomicU64,
+}
+
+// Parse packet of format type:id:endpoint:data.
+fn parse_packet(packet: &str) -> (&str, &str, &str) {
+    let mut parts = packet.splitn(4, ':');
+    let packet_type = parts.next().unwrap_or("");
+    let id = parts.next().unwrap_or("");
+    let _endpoint = parts.next();
+    let data = parts.next().unwrap_or("");
+
+    (packet_type, id, data)
+}
+
+// Split body of xhr-polling request into packets.
+fn unframe(body: &str) -> Vec<String> {
+    if !body.starts_with(FRAME_BOUNDARY) {
+        return vec![body.to_owned()];
+    }
+
+    body.split(FRAME_BOUNDARY)
+        .skip(1)
+        .collect::<Vec<_>>()
+        .chunks(2)
+        .filter_map(|chunk| chunk.get(1).map(|packet| packet.to_string()))
+        .collect()
+}
+
+fn frame(packets: &[String]) -> String {
+    match packets {
+        [packet] => packet.clone(),
+        _ => packets
+            .iter()
+            .map(|packet| {
+                format!(
+                    "{FRAME_BOUNDARY}{}{FRAME_BOUNDARY}{packet}",
+                    packet.chars().count()
+                )
+            })
+            .collect(),
+    }
+}
+
+// Apply ShareJS text ops to text. Positions of ops are in UTF-16 code units.
+pub fn apply_ops(text: &str, ops: &[Value]) -> Result<String> {
+    let mut units: Vec<u16> = text.encode_utf16().collect();
+
+    for op in ops {
+        let position = op["p"].as_u64().context("Op without position.")? as usize;
+
+        if position > units.len() {
+            bail!("Op position {position} out of range.");
+        }
+
+        if let Some(inserted) = op["i"].as_str() {
+            units.splice(position..position, inserted.encode_utf16());
+        } else if let Some(deleted) = op["d"].as_str() {
+            let deleted: Vec<u16> = deleted.encode_utf16().collect();
+            let end = position + deleted.len();
+
+            if end > units.len() || units[position..end] != deleted[..] {
+                bail!("Deleted text does not match the doc.");
+            }
+
+            units.drain(position..end);
+        }
+    }
+
+    String::from_utf16(&units).context("Invalid UTF-16")
+}
+
+impl SocketIo {
+    // Start new session and return handshake response.
+    pub fn handshake(&self, project_id: &str) -> String {
+        let id = format!(
+            "mock{}",
+            self.next_session_id.fetch_add(1, Ordering::SeqCst)
+        );
+
+        self.sessions.lock().unwrap().insert(
+            id.clone(),
+            Session {
+                project_id: project_id.to_owned(),
+                queue: vec!["1::".to_owned()],
+                polls: 0,
+                active: false,
+            },
+        );
+
+        format!("{id}:60:60:xhr-polling")
+    }
+
+    pub fn disconnect(&self, session_id: &str) {
+        self.sessions.lock().unwrap().remove(session_id);
+    }
+
+    fn take_packets(&self, store: &Store, session_id: &str) -> Result<Vec<String>> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(session_id)
+            .context(format!("Session {session_id} not found."))?;
+
+        session.polls += 1;
+
+        if session.polls > 1 && !session.active {
+            let response = json!({
+                "name": "joinProjectResponse",
+                "args": [{
+                    "project": store.project_json(&session.project_id)?,
+                    "permissionsLevel": "owner",
+                    "protocolVersion": 2,
+                }],
+            });
+
+            session.queue.push(format!("5:::{response}"));
+        }
+
+        Ok(std::mem::take(&mut session.queue))
+    }
+
+    // Respond to poll with queued packets.
+    pub async fn poll(&self, store: &Store, session_id: &str) -> Result<String> {
+        let mut packets = self.take_packets(store, session_id)?;
+
+        if packets.is_empty() {
+            tokio::time::sleep(POLL_WAIT).await;
+
+            let mut sessions = self.sessions.lock().unwrap();
+
+            if let Some(session) = sessions.get_mut(session_id) {
+                packets = std::mem::take(&mut session.queue);
+            }
+        }
+
+        if packets.is_empty() {
+            packets.push("8::".to_owned());
+        }
+
+        Ok(frame(&packets))
+    }
+
+    // Handle packets sent by client.
+    pub fn receive(&self, store: &Store, session_id: &str, body: &str) -> Result<()> {
+        for packet in unframe(body) {
+            let (packet_type, id, data) = parse_packet(&packet);
+
+            match packet_type {
+                "0" => self.disconnect(session_id),
+                "5" => {
+                    let event: Value = serde_json::from_str(data)?;
+                    let name = event["name"].as_str().unwrap_or("");
+                    let args = event["args"].as_array().cloned().unwrap_or_default();
+
+                    let project_id = {
+                        let mut sessions = self.sessions.lock().unwrap();
+                        let session = sessions
+                            .get_mut(session_id)
+                            .context(format!("Session {session_id} not found."))?;
+
+                        session.active = true;
+                        session.project_id.clone()
+                    };
+
+                    let ack = self.handle_event(store, &project_id, name, &args);
+
+                    if let Some(ack_id) = id.strip_suffix('+') {
+                        let ack_args = match ack {
+                            Ok(args) => args,
+                            Err(err) => json!([err.to_string()]),
+                        };
+
+                        if let Some(session) = self.sessions.lock().unwrap().get_mut(session_id) {
+                            session.queue.push(format!("6:::{ack_id}+{ack_args}"));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn version(&self, doc_id: &str) -> u64 {
+        *self
+            .versions
+            .lock()
+            .unwrap()
+            .entry(doc_id.to_owned())
+            .or_insert(1)
+    }
+
+    // Handle event and return arguments of its acknowledgement.
+    fn handle_event(
+        &self,
+        store: &Store,
+        project_id: &str,
+        name: &str,
+        args: &[Value],
+    ) -> Result<Value> {
+        let doc_id = args.first().and_then(Value::as_str).unwrap_or("");
+
+        match name {
+            "joinDoc" => {
+                let path = store.find_path(project_id, doc_id, EntityKind::Doc)?;
+                let text = String::from_utf8(store.read(project_id, &path)?)?;
+
+                // Lines are sent as UTF-8 bytes escaped into latin-1 strings.
+                let lines: Vec<String> = text
+                    .split('\n')
+                    .map(|line| line.bytes().map(char::from).collect())
+                    .collect();
+
+                Ok(json!([null, lines, self.version(doc_id), [], {}]))
+            }
+            "applyOtUpdate" => {
+                let update = args.get(1).context("Missing update.")?;
+                let version = self.version(doc_id);
+
+                if update["v"].as_u64() != Some(version) {
+                    bail!("Version mismatch, doc is at version {version}.");
+                }
+
+                let path = store.find_path(project_id, doc_id, EntityKind::Doc)?;
+                let text = String::from_utf8(store.read(project_id, &path)?)?;
+                let ops = update["op"].as_array().cloned().unwrap_or_default();
+
+                store.write(project_id, &path, apply_ops(&text, &ops)?.as_bytes())?;
+                self.versions
+                    .lock()
+                    .unwrap()
+                    .insert(doc_id.to_owned(), version + 1);
+
+                Ok(json!([]))
+            }
+            _ => Ok(json!([])),
+        }
+    }
+}
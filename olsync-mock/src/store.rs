@@ -0,0 +1,286 @@
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    io::{Cursor, Write},
+    path::{Path, PathBuf},
+};
+use zip::{write::FileOptions, ZipWriter};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EntityKind {
+    Doc,
+    File,
+    Folder,
+}
+
+// Projects stored on disk, one directory per project named after the project. Ids of projects
+// and their entities are derived from names and paths, so they are stable between requests.
+pub struct Store {
+    root: PathBuf,
+}
+
+// Mongo-like id derived from given key.
+fn make_id(key: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    format!("{:024x}", hasher.finish())
+}
+
+fn join(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{prefix}/{name}")
+    }
+}
+
+// Files with UTF-8 content are docs, like text files uploaded to Overleaf.
+fn is_doc(path: &Path) -> bool {
+    fs::read(path).is_ok_and(|content| String::from_utf8(content).is_ok())
+}
+
+// Entries of directory sorted by name, as (name, path, is_dir).
+fn sorted_entries(dir: &Path) -> Result<Vec<(String, PathBuf, bool)>> {
+    let mut entries = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        entries.push((name, entry.path(), entry.file_type()?.is_dir()));
+    }
+
+    entries.sort();
+
+    Ok(entries)
+}
+
+impl Store {
+    pub fn new(root: PathBuf) -> Self {
+        Store { root }
+    }
+
+    pub fn entity_id(project_name: &str, path: &str) -> String {
+        make_id(&format!("{project_name}:{path}"))
+    }
+
+    pub fn project_id(project_name: &str) -> String {
+        make_id(&format!("project:{project_name}"))
+    }
+
+    // All projects as (id, name).
+    pub fn projects(&self) -> Result<Vec<(String, String)>> {
+        Ok(sorted_entries(&self.root)?
+            .into_iter()
+            .filter(|(_, _, is_dir)| *is_dir)
+            .map(|(name, _, _)| (Store::project_id(&name), name))
+            .collect())
+    }
+
+    pub fn project_name(&self, project_id: &str) -> Result<String> {
+        self.projects()?
+            .into_iter()
+            .find(|(id, _)| id == project_id)
+            .map(|(_, name)| name)
+            .context(format!("Project {project_id} not found."))
+    }
+
+    fn project_dir(&self, project_id: &str) -> Result<(String, PathBuf)> {
+        let name = self.project_name(project_id)?;
+        let dir = self.root.join(&name);
+        Ok((name, dir))
+    }
+
+    fn folder_json(&self, project_name: &str, dir: &Path, path: &str, name: &str) -> Result<Value> {
+        let mut docs = Vec::new();
+        let mut file_refs = Vec::new();
+        let mut folders = Vec::new();
+
+        for (entry_name, entry_path, is_dir) in sorted_entries(dir)? {
+            let entry_rel = join(path, &entry_name);
+
+            if is_dir {
+                folders.push(self.folder_json(
+                    project_name,
+                    &entry_path,
+                    &entry_rel,
+                    &entry_name,
+                )?);
+                continue;
+            }
+
+            let entity = json!({
+                "_id": Store::entity_id(project_name, &entry_rel),
+                "name": entry_name,
+            });
+
+            if is_doc(&entry_path) {
+                docs.push(entity);
+            } else {
+                file_refs.push(entity);
+            }
+        }
+
+        Ok(json!({
+            "_id": Store::entity_id(project_name, path),
+            "name": name,
+            "docs": docs,
+            "fileRefs": file_refs,
+            "folders": folders,
+        }))
+    }
+
+    // Project data as sent in joinProjectResponse.
+    pub fn project_json(&self, project_id: &str) -> Result<Value> {
+        let (name, dir) = self.project_dir(project_id)?;
+
+        Ok(json!({
+            "_id": project_id,
+            "name": name,
+            "rootFolder": [self.folder_json(&name, &dir, "", "rootFolder")?],
+            "compiler": "pdflatex",
+            "imageName": "texlive-full:2024.1",
+            "rootDoc_id": null,
+            "spellCheckLanguage": "en",
+        }))
+    }
+
+    // Folder as returned when it is created.
+    pub fn folder(&self, project_id: &str, folder_id: &str) -> Result<Value> {
+        let (name, dir) = self.project_dir(project_id)?;
+        let path = self.find_path(project_id, folder_id, EntityKind::Folder)?;
+        let folder_name = path.rsplit('/').next().unwrap_or("").to_owned();
+
+        self.folder_json(&name, &dir.join(&path), &path, &folder_name)
+    }
+
+    // All entities of project as (path, kind).
+    fn entities(&self, dir: &Path, path: &str) -> Result<Vec<(String, EntityKind)>> {
+        let mut entities = Vec::new();
+
+        for (entry_name, entry_path, is_dir) in sorted_entries(dir)? {
+            let entry_rel = join(path, &entry_name);
+
+            if is_dir {
+                entities.extend(self.entities(&entry_path, &entry_rel)?);
+                entities.push((entry_rel, EntityKind::Folder));
+            } else if is_doc(&entry_path) {
+                entities.push((entry_rel, EntityKind::Doc));
+            } else {
+                entities.push((entry_rel, EntityKind::File));
+            }
+        }
+
+        Ok(entities)
+    }
+
+    // Path of entity with given id and kind, relative to project directory. Docs and files are
+    // interchangeable, since they differ only by content.
+    pub fn find_path(&self, project_id: &str, entity_id: &str, kind: EntityKind) -> Result<String> {
+        let (name, dir) = self.project_dir(project_id)?;
+
+        if kind == EntityKind::Folder && entity_id == Store::entity_id(&name, "") {
+            return Ok(String::new());
+        }
+
+        self.entities(&dir, "")?
+            .into_iter()
+            .find(|(path, entity_kind)| {
+                (*entity_kind == EntityKind::Folder) == (kind == EntityKind::Folder)
+                    && Store::entity_id(&name, path) == entity_id
+            })
+            .map(|(path, _)| path)
+            .context(format!("Entity {entity_id} not found."))
+    }
+
+    pub fn read(&self, project_id: &str, path: &str) -> Result<Vec<u8>> {
+        let (_, dir) = self.project_dir(project_id)?;
+        fs::read(dir.join(path)).context(format!("Failed to read {path}."))
+    }
+
+    pub fn write(&self, project_id: &str, path: &str, content: &[u8]) -> Result<()> {
+        let (_, dir) = self.project_dir(project_id)?;
+        fs::write(dir.join(path), content).context(format!("Failed to write {path}."))
+    }
+
+    // Upload file into folder and return its id and kind.
+    pub fn upload(
+        &self,
+        project_id: &str,
+        folder_id: &str,
+        name: &str,
+        content: &[u8],
+    ) -> Result<(String, EntityKind)> {
+        let (project_name, dir) = self.project_dir(project_id)?;
+        let folder_path = self.find_path(project_id, folder_id, EntityKind::Folder)?;
+        let path = join(&folder_path, name);
+
+        if dir.join(&path).is_dir() {
+            bail!("Folder {path} already exists.");
+        }
+
+        fs::write(dir.join(&path), content)?;
+
+        let kind = if is_doc(&dir.join(&path)) {
+            EntityKind::Doc
+        } else {
+            EntityKind::File
+        };
+
+        Ok((Store::entity_id(&project_name, &path), kind))
+    }
+
+    // Create folder inside of parent folder and return its id.
+    pub fn create_folder(
+        &self,
+        project_id: &str,
+        parent_folder_id: &str,
+        name: &str,
+    ) -> Result<String> {
+        let (project_name, dir) = self.project_dir(project_id)?;
+        let parent_path = self.find_path(project_id, parent_folder_id, EntityKind::Folder)?;
+        let path = join(&parent_path, name);
+
+        if dir.join(&path).exists() {
+            bail!("File {path} already exists.");
+        }
+
+        fs::create_dir(dir.join(&path))?;
+
+        Ok(Store::entity_id(&project_name, &path))
+    }
+
+    // Delete doc, file or folder with given id.
+    pub fn delete(&self, project_id: &str, entity_id: &str, kind: EntityKind) -> Result<()> {
+        let (_, dir) = self.project_dir(project_id)?;
+        let path = self.find_path(project_id, entity_id, kind)?;
+
+        if path.is_empty() {
+            bail!("Root folder cannot be deleted.");
+        }
+
+        if kind == EntityKind::Folder {
+            fs::remove_dir_all(dir.join(&path))
+        } else {
+            fs::remove_file(dir.join(&path))
+        }
+        .context(format!("Failed to delete {path}."))
+    }
+
+    // Zip archive of the whole project.
+    pub fn zip(&self, project_id: &str) -> Result<Vec<u8>> {
+        let (_, dir) = self.project_dir(project_id)?;
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+
+        for (path, kind) in self.entities(&dir, "")? {
+            if kind != EntityKind::Folder {
+                zip.start_file(path.as_str(), FileOptions::default())?;
+                zip.write_all(&fs::read(dir.join(&path))?)?;
+            }
+        }
+
+        Ok(zip.finish()?.into_inner())
+    }
+}
@@ -1,11 +0,0 @@
-pub const SESSION_COOKIE_NAME: &str = "overleaf_session2";
-pub const GCLB_COOKIE_NAME: &str = "GCLB";
-
-pub const BASE_URL: &str = "https://www.overleaf.com";
-pub const LOGIN_URL: &str = "https://www.overleaf.com/login";
-pub const PROJECTS_URL: &str = "https://www.overleaf.com/project";
-pub const DOWNLOAD_PROJECT_URL: &str = "https://www.overleaf.com/project/{}/download/zip";
-pub const UPLOAD_FILE_URL: &str = "https://www.overleaf.com/project/{}/upload";
-pub const SOCKET_URL: &str = "https://www.overleaf.com/socket.io/socket.io.js";
-
-pub const ONE_HOUR_IN_SECONDS: u64 = 3600;
@@ -1,8 +1,23 @@
+use anyhow::{Context, Result};
+use chrono::Local;
 use colored::{ColoredString, Colorize};
-use env_logger::fmt::Formatter;
-use log::{Level, Record};
+use env_logger::{fmt::Formatter, Logger, Target, WriteStyle};
+use log::{error, info, Level, Log, Metadata, Record};
+use olsync_core::{
+    batch::BatchResult,
+    progress::{Progress, ProgressEvent, SyncAction, SyncEvent},
+};
+use serde_json::json;
 use spinoff::{spinners, Color, Spinner};
-use std::io::Write;
+use std::{
+    env,
+    fs::OpenOptions,
+    io::{stdout, IsTerminal, Write},
+    path::Path,
+    sync::Mutex,
+};
+
+use crate::output::{json_output, print_json};
 
 fn with_prefix(msg: String) -> String {
     msg.replace("\n", "\n |  ")
@@ -16,6 +31,16 @@ fn error_message(msg: String) -> String {
     format!("{} {}", "[!]".red(), with_prefix(msg).red())
 }
 
+// Print success message of an intermediate step. With JSON output it goes to stderr, so that
+// stdout contains only JSON.
+fn print_success_message(msg: String) {
+    if json_output() {
+        eprintln!("{}", success_message(msg));
+    } else {
+        println!("{}", success_message(msg));
+    }
+}
+
 pub fn custom_log_format(buf: &mut Formatter, record: &Record) -> std::io::Result<()> {
     let level = record.level();
 
@@ -23,7 +48,8 @@ pub fn custom_log_format(buf: &mut Formatter, record: &Record) -> std::io::Resul
         Level::Error => "[!]".red(),
         Level::Warn => "[⚠️]".yellow(),
         Level::Info => ColoredString::from("[*]"),
-        _ => ColoredString::from(""),
+        Level::Debug => "[debug]".dimmed(),
+        Level::Trace => "[trace]".dimmed(),
     };
 
     let msg_with_prefixes: String = with_prefix(record.args().to_string());
@@ -37,32 +63,266 @@ pub fn custom_log_format(buf: &mut Formatter, record: &Record) -> std::io::Resul
     writeln!(buf, "{} {}", level_prefix, message)
 }
 
+// Environment variable with log filters in env_logger syntax, e.g. debug or olsync=trace.
+const LOG_ENV: &str = "OLSYNC_LOG";
+
+// Format of log file, with timestamps and modules the records come from.
+fn log_file_format(buf: &mut Formatter, record: &Record) -> std::io::Result<()> {
+    writeln!(
+        buf,
+        "{} {:<5} {}: {}",
+        Local::now().format("%Y-%m-%dT%H:%M:%S%.3f%:z"),
+        record.level(),
+        record.target(),
+        record.args()
+    )
+}
+
+// Log filters for given number of -v flags. Filters of olsync also apply to olsync_core.
+fn verbose_filters(verbosity: u8) -> &'static str {
+    match verbosity {
+        0 => "info",
+        1 => "info,olsync=debug",
+        _ => "debug,olsync=trace",
+    }
+}
+
+// Logger printing records on stderr and optionally writing them to a log file.
+struct OlsyncLogger {
+    stderr: Logger,
+    file: Option<Logger>,
+}
+
+impl Log for OlsyncLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.stderr.enabled(metadata) || self.file.as_ref().is_some_and(|f| f.enabled(metadata))
+    }
+
+    fn log(&self, record: &Record) {
+        self.stderr.log(record);
+
+        if let Some(file) = &self.file {
+            file.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.stderr.flush();
+
+        if let Some(file) = &self.file {
+            file.flush();
+        }
+    }
+}
+
+// Set up logging. Verbosity given with flags takes precedence over OLSYNC_LOG, which takes
+// precedence over the default of info. Log file gets at least debug records of olsync, so that
+// it can be attached to bug reports.
+pub fn init_logger(verbosity: u8, quiet: bool, log_file: Option<&Path>) -> Result<()> {
+    let env_filters = env::var(LOG_ENV).ok().filter(|filters| !filters.is_empty());
+
+    let stderr_filters = match (quiet, verbosity, &env_filters) {
+        (true, _, _) => "warn",
+        (false, 0, Some(filters)) => filters,
+        (false, verbosity, _) => verbose_filters(verbosity),
+    };
+
+    let stderr = env_logger::Builder::new()
+        .format(custom_log_format)
+        .parse_filters(stderr_filters)
+        .build();
+
+    let file = match log_file {
+        Some(path) => {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .context(format!("Failed to open log file {}.", path.display()))?;
+
+            let file_filters = match (verbosity, &env_filters) {
+                (0, Some(filters)) => filters,
+                (verbosity, _) => verbose_filters(verbosity.max(1)),
+            };
+
+            Some(
+                env_logger::Builder::new()
+                    .format(log_file_format)
+                    .parse_filters(file_filters)
+                    .write_style(WriteStyle::Never)
+                    .target(Target::Pipe(Box::new(file)))
+                    .build(),
+            )
+        }
+        None => None,
+    };
+
+    let max_level = stderr
+        .filter()
+        .max(file.as_ref().map_or(log::LevelFilter::Off, Logger::filter));
+
+    log::set_boxed_logger(Box::new(OlsyncLogger { stderr, file }))?;
+    log::set_max_level(max_level);
+
+    Ok(())
+}
+
+// Print final success message of a command. With JSON output it goes to stderr, since the result
+// of the command is printed as JSON on stdout.
 #[macro_export]
 macro_rules! success {
     ($($arg:tt)*) => {{
         use $crate::custom_log::success_message;
+        use $crate::output::json_output;
         let message = format!($($arg)*);
-        println!("{}", success_message(message));
+        if json_output() {
+            eprintln!("{}", success_message(message));
+        } else {
+            println!("{}", success_message(message));
+        }
     }};
 }
 
+// Print compact, timestamped line about the event, or the event as JSON.
+pub fn print_sync_event(event: &SyncEvent) {
+    if json_output() {
+        print_json(event);
+        return;
+    }
+
+    let time = Local::now().format("%H:%M:%S").to_string().dimmed();
+
+    let action = format!("{:<8}", format!("{:?}", event.action).to_lowercase());
+    let action = match event.action {
+        SyncAction::Skipped => action.yellow(),
+        SyncAction::Failed => action.red(),
+        _ => action.green(),
+    };
+
+    match (&event.detail, event.action) {
+        (Some(detail), _) if event.path.is_empty() => println!("{time} {action} {detail}"),
+        (Some(detail), SyncAction::Moved) => println!("{time} {action} {} -> {detail}", event.path),
+        (Some(detail), _) => println!("{time} {action} {}: {detail}", event.path),
+        (None, _) => println!("{time} {action} {}", event.path),
+    }
+}
+
+// Spinners are shown only on terminal and never with JSON output, which must not be mixed with
+// spinner frames.
+fn spinners_enabled() -> bool {
+    !json_output() && stdout().is_terminal()
+}
+
+// Spinner shown while operation is running. When spinners are disabled, messages are printed as
+// plain log lines instead.
 pub struct OlSpinner {
-    spinner: Spinner,
+    spinner: Option<Spinner>,
 }
 
 impl OlSpinner {
     pub fn new(message: String) -> Self {
+        if !spinners_enabled() {
+            info!("{message}");
+            return OlSpinner { spinner: None };
+        }
+
         let spinner = Spinner::new(spinners::Aesthetic, message, Color::White);
-        OlSpinner { spinner }
+        OlSpinner {
+            spinner: Some(spinner),
+        }
     }
 
     pub fn stop_with_success(&mut self, message: String) {
-        self.spinner
-            .stop_with_message(success_message(message).as_str());
+        match &mut self.spinner {
+            Some(spinner) => spinner.stop_with_message(success_message(message).as_str()),
+            None => print_success_message(message),
+        }
     }
 
     pub fn stop_with_error(&mut self, message: String) {
-        self.spinner
-            .stop_with_message(error_message(message).as_str());
+        match &mut self.spinner {
+            Some(spinner) => spinner.stop_with_message(error_message(message).as_str()),
+            None => error!("{message}"),
+        }
+    }
+
+    pub fn update_text(&mut self, message: String) {
+        match &mut self.spinner {
+            Some(spinner) => spinner.update_text(message),
+            None => info!("{message}"),
+        }
+    }
+}
+
+// Present progress reported by olsync-core with spinners. Outcomes of operations which were not
+// announced as started are printed as plain messages.
+#[derive(Default)]
+pub struct SpinnerProgress {
+    spinner: Mutex<Option<OlSpinner>>,
+}
+
+impl Progress for SpinnerProgress {
+    fn report(&self, event: ProgressEvent) {
+        let mut spinner = self.spinner.lock().unwrap();
+
+        match (event, spinner.take()) {
+            (ProgressEvent::Started(message), Some(mut running)) => {
+                running.update_text(message);
+                *spinner = Some(running);
+            }
+            (ProgressEvent::Started(message), None) => *spinner = Some(OlSpinner::new(message)),
+            (ProgressEvent::Finished(message), Some(mut running)) => {
+                running.stop_with_success(message)
+            }
+            (ProgressEvent::Finished(message), None) => print_success_message(message),
+            (ProgressEvent::Failed(message), Some(mut running)) => running.stop_with_error(message),
+            (ProgressEvent::Failed(message), None) => error!("{message}"),
+        }
+    }
+}
+
+// Print table summarizing results of batch operation.
+pub fn print_batch_summary(results: &[BatchResult]) {
+    if json_output() {
+        let results: Vec<_> = results
+            .iter()
+            .map(|batch_result| match &batch_result.result {
+                Ok(message) => json!({
+                    "project": batch_result.project,
+                    "success": true,
+                    "message": message,
+                }),
+                Err(err) => json!({
+                    "project": batch_result.project,
+                    "success": false,
+                    "message": err.to_string(),
+                }),
+            })
+            .collect();
+
+        print_json(&json!({ "results": results }));
+        return;
+    }
+
+    let name_width = results
+        .iter()
+        .map(|batch_result| batch_result.project.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Project".len())
+        + 2;
+
+    println!("\n{:<name_width$}{:<8}Details", "Project", "Status");
+
+    for batch_result in results {
+        let (status, details) = match &batch_result.result {
+            Ok(message) => ("OK".green(), message.clone()),
+            Err(err) => ("FAILED".red(), err.to_string().replace('\n', " ")),
+        };
+
+        println!(
+            "{:<name_width$}{:<8}{details}",
+            batch_result.project.name, status
+        );
     }
 }
@@ -0,0 +1,521 @@
+use colored::Colorize;
+use serde::Serialize;
+use std::{
+    env, fmt,
+    path::{Path, PathBuf},
+};
+
+// TeX wraps log lines longer than this many bytes.
+const LOG_LINE_WIDTH: usize = 79;
+
+// How many lines after an error message are searched for the `l.<line>` marker.
+const ERROR_CONTEXT_LINES: usize = 10;
+
+// Directory in which Overleaf compiles projects.
+const REMOTE_COMPILE_DIR: &str = "/compile/";
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiagnosticKind {
+    Error,
+    Warning,
+    OverfullBox,
+    UnderfullBox,
+    UndefinedReference,
+    UndefinedCitation,
+}
+
+impl DiagnosticKind {
+    pub fn severity(&self) -> Severity {
+        match self {
+            DiagnosticKind::Error => Severity::Error,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub kind: DiagnosticKind,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(kind: DiagnosticKind, file: Option<String>, line: Option<u32>, message: String) -> Self {
+        Diagnostic {
+            severity: kind.severity(),
+            kind,
+            file,
+            line,
+            message,
+        }
+    }
+}
+
+// Formats diagnostic the way compilers do, i.e. `file:line: severity: message`.
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let location = match (&self.file, self.line) {
+            (Some(file), Some(line)) => format!("{file}:{line}:"),
+            (Some(file), None) => format!("{file}:"),
+            _ => "output.log:".to_owned(),
+        };
+
+        let severity = match self.severity {
+            Severity::Error => "error:".red().bold(),
+            Severity::Warning => "warning:".yellow().bold(),
+        };
+
+        write!(f, "{} {} {}", location.bold(), severity, self.message)
+    }
+}
+
+// Join lines wrapped by TeX back into single lines.
+fn unwrap_lines(log: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for line in log.lines() {
+        current.push_str(line);
+
+        if line.len() != LOG_LINE_WIDTH {
+            lines.push(std::mem::take(&mut current));
+        }
+    }
+
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    lines
+}
+
+// Parse line number from the beginning of text, e.g. `12--14` gives 12.
+fn parse_leading_number(text: &str) -> Option<u32> {
+    let digits: String = text.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+// Parse `on input line N` or `at line(s) N` annotations from message.
+fn parse_message_line(message: &str) -> Option<u32> {
+    ["on input line ", "at lines ", "at line "]
+        .iter()
+        .find_map(|marker| {
+            message
+                .find(marker)
+                .and_then(|idx| parse_leading_number(&message[idx + marker.len()..]))
+        })
+}
+
+// Parse `./file.tex:12: message` lines produced with -file-line-error.
+fn parse_file_line_error(line: &str) -> Option<(String, u32, String)> {
+    if !line.starts_with("./") && !line.starts_with('/') {
+        return None;
+    }
+
+    let mut search_from = 0;
+
+    while let Some(idx) = line[search_from..].find(':').map(|i| i + search_from) {
+        let rest = &line[idx + 1..];
+
+        if let Some(number) = parse_leading_number(rest) {
+            let digits = number.to_string().len();
+
+            if let Some(message) = rest[digits..].strip_prefix(": ") {
+                return Some((line[..idx].to_owned(), number, message.to_owned()));
+            }
+        }
+
+        search_from = idx + 1;
+    }
+
+    None
+}
+
+// Returns warning message if line starts a LaTeX, class or package warning.
+fn parse_warning_start(line: &str) -> Option<(String, Option<String>)> {
+    if let Some(message) = line.strip_prefix("LaTeX Font Warning: ") {
+        return Some((message.to_owned(), Some("Font".to_owned())));
+    }
+
+    for prefix in ["LaTeX Warning: ", "pdfTeX warning: "] {
+        if let Some(message) = line.strip_prefix(prefix) {
+            return Some((message.to_owned(), None));
+        }
+    }
+
+    for prefix in ["Package ", "Class "] {
+        if let Some(rest) = line.strip_prefix(prefix) {
+            if let Some(idx) = rest.find(" Warning: ") {
+                let name = rest[..idx].to_owned();
+                let message = rest[idx + " Warning: ".len()..].to_owned();
+
+                if !name.contains(' ') {
+                    return Some((message, Some(name)));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+// Find the `l.<line>` marker printed below error message at given index. Returns the line
+// number and index of the last line of source context, which should be skipped as it may
+// contain unbalanced parentheses breaking tracking of the current file.
+fn find_error_context(lines: &[String], error_idx: usize) -> (Option<u32>, usize) {
+    let context = lines
+        .iter()
+        .enumerate()
+        .skip(error_idx + 1)
+        .take(ERROR_CONTEXT_LINES)
+        .find_map(|(idx, line)| {
+            line.strip_prefix("l.")
+                .and_then(parse_leading_number)
+                .map(|number| (idx, number))
+        });
+
+    match context {
+        Some((idx, number)) => {
+            let continued = lines
+                .get(idx + 1)
+                .is_some_and(|line| line.starts_with(char::is_whitespace));
+
+            (Some(number), if continued { idx + 1 } else { idx })
+        }
+        None => (None, error_idx),
+    }
+}
+
+#[derive(Default)]
+struct LogParser {
+    // Stack of files opened with `(` in the log. None stands for parentheses not opening a file.
+    file_stack: Vec<Option<String>>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl LogParser {
+    fn current_file(&self) -> Option<String> {
+        self.file_stack.iter().rev().flatten().next().cloned()
+    }
+
+    // Track files opened and closed in the line.
+    fn update_file_stack(&mut self, line: &str) {
+        for (idx, c) in line.char_indices() {
+            match c {
+                '(' => {
+                    let token: String = line[idx + 1..]
+                        .chars()
+                        .take_while(|c| !c.is_whitespace() && *c != '(' && *c != ')')
+                        .collect();
+
+                    if token.contains('.') || token.contains('/') {
+                        self.file_stack
+                            .push(Some(token.trim_matches('"').to_owned()));
+                    } else {
+                        self.file_stack.push(None);
+                    }
+                }
+                ')' => {
+                    self.file_stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn parse(mut self, lines: &[String]) -> Vec<Diagnostic> {
+        let mut idx = 0;
+
+        while idx < lines.len() {
+            let line = lines[idx].as_str();
+
+            if let Some(message) = line.strip_prefix("! ") {
+                let (line_number, context_end) = find_error_context(lines, idx);
+
+                self.diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::Error,
+                    self.current_file(),
+                    line_number,
+                    message.trim().to_owned(),
+                ));
+
+                idx = context_end;
+            } else if let Some((file, line_number, message)) = parse_file_line_error(line) {
+                self.diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::Error,
+                    Some(file),
+                    Some(line_number),
+                    message.trim().to_owned(),
+                ));
+
+                idx = find_error_context(lines, idx).1;
+            } else if line.starts_with("Overfull \\") || line.starts_with("Underfull \\") {
+                let kind = if line.starts_with("Overfull") {
+                    DiagnosticKind::OverfullBox
+                } else {
+                    DiagnosticKind::UnderfullBox
+                };
+
+                self.diagnostics.push(Diagnostic::new(
+                    kind,
+                    self.current_file(),
+                    parse_message_line(line),
+                    line.trim().to_owned(),
+                ));
+
+                // Skip the offending box content printed below, up to the next empty line.
+                if lines.get(idx + 1).is_some_and(|l| l.starts_with("[]")) {
+                    while idx + 1 < lines.len() && !lines[idx + 1].trim().is_empty() {
+                        idx += 1;
+                    }
+                }
+            } else if let Some((mut message, package)) = parse_warning_start(line) {
+                // Warning messages continue until an empty line. Package warnings prefix
+                // continuation lines with the package name in parentheses.
+                while idx + 1 < lines.len() && !lines[idx + 1].trim().is_empty() {
+                    let next = lines[idx + 1].as_str();
+
+                    let continuation = match &package {
+                        Some(name) => match next.strip_prefix(&format!("({name})")) {
+                            Some(rest) => rest,
+                            None => break,
+                        },
+                        None if !next.starts_with('(') && !next.starts_with(')') => next,
+                        None => break,
+                    };
+
+                    message.push(' ');
+                    message.push_str(continuation.trim());
+                    idx += 1;
+                }
+
+                let kind = if message.starts_with("Reference `") && message.contains("undefined") {
+                    DiagnosticKind::UndefinedReference
+                } else if message.starts_with("Citation `") && message.contains("undefined") {
+                    DiagnosticKind::UndefinedCitation
+                } else {
+                    DiagnosticKind::Warning
+                };
+
+                self.diagnostics.push(Diagnostic::new(
+                    kind,
+                    self.current_file(),
+                    parse_message_line(&message),
+                    message.trim().to_owned(),
+                ));
+            } else {
+                self.update_file_stack(line);
+            }
+
+            idx += 1;
+        }
+
+        self.diagnostics
+    }
+}
+
+// Parse errors, warnings, bad boxes and undefined references from LaTeX log.
+pub fn parse_latex_log(log: &str) -> Vec<Diagnostic> {
+    LogParser::default().parse(&unwrap_lines(log))
+}
+
+// Map file name from compile log to path in local repository. Returns None for files
+// which do not belong to the project, e.g. TeX Live packages.
+pub fn to_local_path(log_file: &str, repo_root: &Path) -> Option<PathBuf> {
+    let relative = log_file
+        .strip_prefix(REMOTE_COMPILE_DIR)
+        .or_else(|| log_file.strip_prefix("./"))
+        .unwrap_or(log_file);
+
+    if relative.is_empty() || Path::new(relative).is_absolute() {
+        return None;
+    }
+
+    Some(repo_root.join(relative))
+}
+
+// Replace remote file names in diagnostics with local paths, relative to current directory
+// whenever possible.
+pub fn resolve_local_paths(diagnostics: &mut [Diagnostic], repo_root: &Path) {
+    let current_dir = env::current_dir().ok();
+
+    for diagnostic in diagnostics.iter_mut() {
+        let local_path = diagnostic
+            .file
+            .as_deref()
+            .and_then(|file| to_local_path(file, repo_root));
+
+        if let Some(path) = local_path {
+            let display_path = current_dir
+                .as_ref()
+                .and_then(|dir| path.strip_prefix(dir).ok())
+                .unwrap_or(&path);
+
+            diagnostic.file = display_path.to_str().map(str::to_owned);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(diagnostics: &[Diagnostic]) -> Vec<(DiagnosticKind, Option<&str>, Option<u32>)> {
+        diagnostics
+            .iter()
+            .map(|d| (d.kind, d.file.as_deref(), d.line))
+            .collect()
+    }
+
+    #[test]
+    fn file_line_errors_are_parsed() {
+        let log = "(./main.tex\n\
+                   ./main.tex:12: Undefined control sequence.\n\
+                   l.12 \\foo\n\
+                   \x20         bar\n\
+                   )\n";
+
+        let diagnostics = parse_latex_log(log);
+
+        assert_eq!(
+            summary(&diagnostics),
+            vec![(DiagnosticKind::Error, Some("./main.tex"), Some(12))]
+        );
+        assert_eq!(diagnostics[0].message, "Undefined control sequence.");
+    }
+
+    #[test]
+    fn errors_are_attributed_to_innermost_open_file() {
+        let log = "(/compile/main.tex (/usr/share/texlive/article.cls\n\
+                   Document Class: article (see the documentation)\n\
+                   ) (./chapters/intro.tex\n\
+                   ! Missing $ inserted.\n\
+                   <inserted text>\n\
+                   \x20               $\n\
+                   l.7 x^(\n\
+                   \x20     2\n\
+                   )\n\
+                   ! Emergency stop.\n\
+                   )\n";
+
+        let diagnostics = parse_latex_log(log);
+
+        assert_eq!(
+            summary(&diagnostics),
+            vec![
+                (DiagnosticKind::Error, Some("./chapters/intro.tex"), Some(7)),
+                (DiagnosticKind::Error, Some("/compile/main.tex"), None),
+            ]
+        );
+        assert_eq!(diagnostics[0].message, "Missing $ inserted.");
+    }
+
+    #[test]
+    fn latex_and_package_warnings_are_parsed() {
+        let log = "(./main.tex\n\
+                   LaTeX Warning: Reference `fig:plot' on page 2 undefined on input line 31.\n\
+                   \n\
+                   Package hyperref Warning: Token not allowed in a PDF string (Unicode):\n\
+                   (hyperref)                removing `math shift' on input line 40.\n\
+                   \n\
+                   LaTeX Warning: Citation `knuth84' on page 1 undefined on input line 5.\n\
+                   \n\
+                   )\n";
+
+        let diagnostics = parse_latex_log(log);
+
+        assert_eq!(
+            summary(&diagnostics),
+            vec![
+                (
+                    DiagnosticKind::UndefinedReference,
+                    Some("./main.tex"),
+                    Some(31)
+                ),
+                (DiagnosticKind::Warning, Some("./main.tex"), Some(40)),
+                (
+                    DiagnosticKind::UndefinedCitation,
+                    Some("./main.tex"),
+                    Some(5)
+                ),
+            ]
+        );
+        assert_eq!(
+            diagnostics[1].message,
+            "Token not allowed in a PDF string (Unicode): removing `math shift' on input line 40."
+        );
+    }
+
+    #[test]
+    fn wrapped_lines_are_joined() {
+        let warning = "LaTeX Warning: Reference `sec:a-very-long-label-name-which-goes-on-and-on' \
+                       on page 3 undefined on input line 120.";
+        let (first, rest) = warning.split_at(LOG_LINE_WIDTH);
+        let log = format!("{first}\n{rest}\n\n");
+
+        assert_eq!(unwrap_lines(&log), vec![warning, ""]);
+
+        let diagnostics = parse_latex_log(&log);
+
+        assert_eq!(
+            summary(&diagnostics),
+            vec![(DiagnosticKind::UndefinedReference, None, Some(120))]
+        );
+        assert_eq!(
+            Some(diagnostics[0].message.as_str()),
+            warning.strip_prefix("LaTeX Warning: ")
+        );
+    }
+
+    #[test]
+    fn bad_boxes_are_parsed() {
+        let log = "(./main.tex\n\
+                   Overfull \\hbox (15.0pt too wide) in paragraph at lines 20--22\n\
+                   []\\OT1/cmr/m/n/10 Text (with parenthesis\n\
+                   \n\
+                   Underfull \\vbox (badness 10000) has occurred while \\output is active []\n\
+                   \n\
+                   )\n\
+                   LaTeX Warning: There were undefined references.\n";
+
+        let diagnostics = parse_latex_log(log);
+
+        assert_eq!(
+            summary(&diagnostics),
+            vec![
+                (DiagnosticKind::OverfullBox, Some("./main.tex"), Some(20)),
+                (DiagnosticKind::UnderfullBox, Some("./main.tex"), None),
+                (DiagnosticKind::Warning, None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_project_files_map_to_local_paths() {
+        let repo_root = Path::new("/home/user/thesis");
+
+        assert_eq!(
+            to_local_path("/compile/chapters/intro.tex", repo_root),
+            Some(repo_root.join("chapters/intro.tex"))
+        );
+        assert_eq!(
+            to_local_path("./main.tex", repo_root),
+            Some(repo_root.join("main.tex"))
+        );
+        assert_eq!(
+            to_local_path("/usr/share/texlive/article.cls", repo_root),
+            None
+        );
+    }
+}
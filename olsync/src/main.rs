@@ -1,36 +1,131 @@
-pub mod auth;
-pub mod constants;
 pub mod custom_log;
-pub mod overleaf_client;
-pub mod repository;
-pub mod utils;
+pub mod latex_log;
+pub mod output;
+pub mod prompt;
 
 use crate::{
+    custom_log::{init_logger, print_batch_summary, print_sync_event, OlSpinner, SpinnerProgress},
+    latex_log::{parse_latex_log, resolve_local_paths},
+    output::{enable_json_output, json_output, print_json, print_json_error},
+    prompt::{assume_yes, confirm, disable_prompts, is_interactive, select},
+};
+
+use olsync_core::{
+    archive::archive_all_projects,
     auth::{
         get_session_info, get_session_info_from_browser, get_session_info_from_file,
         remove_session_info,
     },
-    custom_log::{custom_log_format, OlSpinner},
-    overleaf_client::OverleafClient,
+    backup::{list_backups, parse_age, prune_backups, restore_backup},
+    batch::{read_ids_file, run_batch, select_projects, BatchOperation, ProjectFilter},
+    config::{
+        current_settings, global_config_path, init_settings, repo_config_path, set_config_value,
+        Settings, CONFIG_KEYS,
+    },
+    constants::BASE_URL,
+    daemon::{default_socket_path, run_daemon},
+    error::{ErrorKind, OlsyncError},
+    follow::follow_project_changes,
+    overleaf_client::{
+        HistoryUpdate, Label, OverleafClient, Project, ProjectSettings, ProjectSettingsUpdate,
+        SessionInfo, Tag,
+    },
+    progress::SyncEvent,
     repository::{
-        create_local_backup, download_project, get_project_info, get_repo_root,
-        init_olsync_repository, is_olsync_repository, push_files, wipe_project,
+        compile_project, create_label_on_latest_version, create_local_backup, download_output_file,
+        download_project, download_project_version, fetch_compile_log, fetch_project_details,
+        get_last_compile, get_last_pull_timestamp, get_project_info, get_repo_root,
+        init_olsync_repository, is_olsync_repository, pull_files, pull_project, push_files,
+        save_compile_output_path, save_last_compile, save_last_pull_timestamp, save_project_info,
+        save_project_settings, wipe_project,
     },
     utils::path_to_str,
+    watch::watch_repository,
 };
 
-use anyhow::{anyhow, bail, Result};
-use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{Local, TimeZone};
+use clap::{value_parser, Arg, ArgAction, ArgGroup, ArgMatches, Command};
 use colored::Colorize;
-use log::{error, LevelFilter};
-use std::path::PathBuf;
+use log::{error, warn};
+use serde_json::json;
+use std::{
+    env,
+    ffi::OsString,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+// Permissions of project members accepted by share subcommands.
+const PERMISSIONS: [&str; 3] = ["read-only", "read-write", "review"];
+
+// Name under which olsync runs as daemon, e.g. when installed as a link to olsync.
+const DAEMON_BINARY_NAME: &str = "olsyncd";
 
 #[tokio::main]
 async fn main() {
+    let mut args: Vec<OsString> = env::args_os().collect();
+
+    if args
+        .first()
+        .and_then(|arg| Path::new(arg).file_stem())
+        .is_some_and(|name| name == DAEMON_BINARY_NAME)
+    {
+        args.insert(1, "daemon".into());
+    }
+
     let matches = Command::new("olsync")
         .version("0.3.0")
         .author("Katzper Michno <katzper.michno@gmail.com>")
         .about("CLI for synchronizing LaTeX projects between Overleaf and your local machine")
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .help("Print results as JSON on stdout and messages on stderr")
+                .global(true)
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("yes")
+                .short('y')
+                .long("yes")
+                .help("Answer yes to all confirm prompts")
+                .global(true)
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("non-interactive")
+                .long("non-interactive")
+                .help("Never prompt, fail instead [default when stdin is not a terminal]")
+                .global(true)
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .help("Print debug messages, including HTTP requests; use -vv for even more")
+                .global(true)
+                .action(ArgAction::Count),
+        )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .help("Print only warnings and errors")
+                .global(true)
+                .conflicts_with("verbose")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("log-file")
+                .long("log-file")
+                .help("Append timestamped debug logs to the file")
+                .global(true)
+                .value_parser(value_parser!(PathBuf)),
+        )
         .subcommand(Command::new("whoami").about("Print current session info"))
         .subcommand(Command::new("login").about("Log into Overleaf account"))
         .subcommand(Command::new("logout").about("Log out of currently used Overleaf account"))
@@ -48,6 +143,13 @@ async fn main() {
                     ArgGroup::new("Project key")
                         .args(["name", "id"])
                         .multiple(false),
+                )
+                .arg(
+                    Arg::new("tag")
+                        .short('t')
+                        .long("tag")
+                        .help("Only offer projects with tag for selection")
+                        .conflicts_with("Project key"),
                 ),
         )
         .subcommand(
@@ -64,11 +166,21 @@ async fn main() {
                         .long("force")
                         .help("Skip confirm prompt")
                         .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("label")
+                        .long("label")
+                        .help("Create history label on the version produced by the push"),
                 ),
         )
         .subcommand(
             Command::new("pull")
                 .about("Override local state with remote project")
+                .arg(
+                    Arg::new("files")
+                        .help("Paths of files or folders to pull, whole project if empty")
+                        .action(ArgAction::Append),
+                )
                 .arg(
                     Arg::new("no-backup")
                         .long("no-backup")
@@ -82,61 +194,585 @@ async fn main() {
                         .action(ArgAction::SetTrue),
                 ),
         )
-        .get_matches();
+        .subcommand(
+            Command::new("compile")
+                .about("Compile remote project and download the PDF")
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .help("Where to save the PDF, relative to project root [default: output.pdf]"),
+                )
+                .arg(
+                    Arg::new("log")
+                        .long("log")
+                        .help("Fetch compile log and print errors and warnings")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("output")
+                .about("Download output files of the last remote compile")
+                .arg(
+                    Arg::new("files")
+                        .help("List of output files to download, e.g. output.bbl or synctex.gz")
+                        .action(ArgAction::Append),
+                )
+                .arg(
+                    Arg::new("dir")
+                        .short('d')
+                        .long("dir")
+                        .help("Directory to save output files in, relative to project root")
+                        .default_value("."),
+                ),
+        )
+        .subcommand(
+            Command::new("settings")
+                .about("Show or change remote project settings")
+                .arg(
+                    Arg::new("compiler")
+                        .long("compiler")
+                        .help("Set compiler")
+                        .value_parser(["pdflatex", "xelatex", "lualatex", "latex"]),
+                )
+                .arg(
+                    Arg::new("main-document")
+                        .long("main-document")
+                        .help("Set main document, path relative to project root"),
+                )
+                .arg(
+                    Arg::new("texlive")
+                        .long("texlive")
+                        .help("Set TeX Live image, e.g. texlive-full:2024.1"),
+                )
+                .arg(
+                    Arg::new("spell-check")
+                        .long("spell-check")
+                        .help("Set spell check language, e.g. en_GB"),
+                )
+                .arg(Arg::new("name").long("name").help("Rename the project")),
+        )
+        .subcommand(
+            Command::new("log")
+                .about("Show history of remote project")
+                .arg(
+                    Arg::new("limit")
+                        .short('n')
+                        .long("limit")
+                        .help("Maximum number of updates to show")
+                        .value_parser(value_parser!(u32))
+                        .default_value("20"),
+                )
+                .arg(
+                    Arg::new("before")
+                        .long("before")
+                        .help("Only show updates older than timestamp (in milliseconds)")
+                        .value_parser(value_parser!(i64)),
+                )
+                .arg(
+                    Arg::new("since-pull")
+                        .long("since-pull")
+                        .help("Only show updates made since the last clone or pull")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("labels")
+                        .long("labels")
+                        .help("List history labels instead of updates")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("checkout")
+                .about("Download project as of a past version")
+                .arg(
+                    Arg::new("files")
+                        .help("List of files to download, whole project if empty")
+                        .action(ArgAction::Append),
+                )
+                .arg(
+                    Arg::new("version")
+                        .long("version")
+                        .help("History version")
+                        .value_parser(value_parser!(u64)),
+                )
+                .arg(Arg::new("label").long("label").help("History label"))
+                .group(
+                    ArgGroup::new("Version key")
+                        .args(["version", "label"])
+                        .required(true)
+                        .multiple(false),
+                )
+                .arg(
+                    Arg::new("into")
+                        .long("into")
+                        .help("Download into directory instead of overriding local state"),
+                )
+                .arg(
+                    Arg::new("no-backup")
+                        .long("no-backup")
+                        .help("Skip creating backup of local state before overriding it")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("force")
+                        .long("force")
+                        .help("Skip confirm prompt")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("restore-file")
+                .about("Restore file in remote project to a past version")
+                .arg(
+                    Arg::new("path")
+                        .help("Path of file to restore, relative to project root")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("version")
+                        .long("version")
+                        .help("History version")
+                        .value_parser(value_parser!(u64)),
+                )
+                .arg(Arg::new("label").long("label").help("History label"))
+                .group(
+                    ArgGroup::new("Version key")
+                        .args(["version", "label"])
+                        .required(true)
+                        .multiple(false),
+                )
+                .arg(
+                    Arg::new("force")
+                        .long("force")
+                        .help("Skip confirm prompt")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("label")
+                .about("Manage history labels of remote project")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("add")
+                        .about("Create history label on the current version")
+                        .arg(Arg::new("name").help("Label name").required(true))
+                        .arg(
+                            Arg::new("version")
+                                .long("version")
+                                .help("Label given version instead of the current one")
+                                .value_parser(value_parser!(u64)),
+                        ),
+                )
+                .subcommand(
+                    Command::new("rm")
+                        .about("Delete history label")
+                        .arg(Arg::new("name").help("Label name").required(true))
+                        .arg(
+                            Arg::new("version")
+                                .long("version")
+                                .help("Version of the label, if there are several with this name")
+                                .value_parser(value_parser!(u64)),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("share")
+                .about("Manage collaborators and sharing of remote project")
+                .subcommand_required(true)
+                .subcommand(Command::new("list").about("List project members and pending invites"))
+                .subcommand(
+                    Command::new("invite")
+                        .about("Invite users to the project")
+                        .arg(
+                            Arg::new("emails")
+                                .help("List of emails to invite")
+                                .action(ArgAction::Append)
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("permission")
+                                .short('p')
+                                .long("permission")
+                                .help("Permission of invited users")
+                                .value_parser(PERMISSIONS)
+                                .default_value("read-write"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("set")
+                        .about("Change permission of project member")
+                        .arg(Arg::new("email").help("Member email").required(true))
+                        .arg(
+                            Arg::new("permission")
+                                .short('p')
+                                .long("permission")
+                                .help("New permission of the member")
+                                .value_parser(PERMISSIONS)
+                                .required(true),
+                        ),
+                )
+                .subcommand(
+                    Command::new("rm")
+                        .about("Remove member or revoke pending invite")
+                        .arg(Arg::new("email").help("Member email").required(true)),
+                )
+                .subcommand(
+                    Command::new("transfer")
+                        .about("Transfer project ownership to a member")
+                        .arg(Arg::new("email").help("Member email").required(true))
+                        .arg(
+                            Arg::new("force")
+                                .long("force")
+                                .help("Skip confirm prompt")
+                                .action(ArgAction::SetTrue),
+                        ),
+                )
+                .subcommand(
+                    Command::new("link")
+                        .about("Turn link sharing on or off")
+                        .arg(
+                            Arg::new("state")
+                                .help("Whether link sharing is enabled")
+                                .value_parser(["on", "off"])
+                                .required(true),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("batch")
+                .about("Run operation for many remote projects at once")
+                .arg(
+                    Arg::new("operation")
+                        .help("Operation to run for each project")
+                        .value_parser(["clone", "pull", "download-zip", "compile"])
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("tag")
+                        .short('t')
+                        .long("tag")
+                        .help("Select projects with tag"),
+                )
+                .arg(
+                    Arg::new("name")
+                        .short('n')
+                        .long("name")
+                        .help("Select projects with name matching pattern, e.g. 'thesis-*'"),
+                )
+                .arg(
+                    Arg::new("ids-file")
+                        .long("ids-file")
+                        .help("Select projects with ids listed in file, one per line"),
+                )
+                .group(
+                    ArgGroup::new("Project filter")
+                        .args(["tag", "name", "ids-file"])
+                        .required(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::new("dir")
+                        .short('d')
+                        .long("dir")
+                        .help("Directory containing cloned projects and downloaded files")
+                        .default_value("."),
+                )
+                .arg(
+                    Arg::new("jobs")
+                        .short('j')
+                        .long("jobs")
+                        .help("Maximum number of projects processed at once")
+                        .value_parser(value_parser!(usize))
+                        .default_value("4"),
+                )
+                .arg(
+                    Arg::new("no-backup")
+                        .long("no-backup")
+                        .help("Skip creating backups of local state before pulling")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("backup")
+                .about("Manage backups of local state created before pull and checkout")
+                .subcommand_required(true)
+                .subcommand(Command::new("list").about("List backups with their sizes"))
+                .subcommand(
+                    Command::new("restore")
+                        .about("Override local state with backup, after backing up current one")
+                        .arg(
+                            Arg::new("id")
+                                .help("Id of backup, as printed by backup list")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("force")
+                                .long("force")
+                                .help("Skip confirm prompt")
+                                .action(ArgAction::SetTrue),
+                        ),
+                )
+                .subcommand(
+                    Command::new("prune")
+                        .about("Remove old backups")
+                        .arg(
+                            Arg::new("keep")
+                                .long("keep")
+                                .help("Number of the newest backups to keep")
+                                .value_parser(value_parser!(usize)),
+                        )
+                        .arg(
+                            Arg::new("older-than")
+                                .long("older-than")
+                                .help("Remove backups older than given age, e.g. 30d or 12h"),
+                        )
+                        .group(
+                            ArgGroup::new("Retention")
+                                .args(["keep", "older-than"])
+                                .required(true)
+                                .multiple(true),
+                        )
+                        .arg(
+                            Arg::new("force")
+                                .long("force")
+                                .help("Skip confirm prompt")
+                                .action(ArgAction::SetTrue),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("backup-all")
+                .about("Download all projects as zip archives into directory")
+                .arg(
+                    Arg::new("dir")
+                        .help("Directory to store archives and manifest in")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("list")
+                .about("List remote projects")
+                .arg(
+                    Arg::new("tag")
+                        .short('t')
+                        .long("tag")
+                        .help("Only list projects with tag"),
+                )
+                .arg(
+                    Arg::new("name")
+                        .short('n')
+                        .long("name")
+                        .help("Only list projects with name matching pattern, e.g. 'thesis-*'"),
+                ),
+        )
+        .subcommand(
+            Command::new("tag")
+                .about("Manage tags of remote projects")
+                .subcommand_required(true)
+                .subcommand(Command::new("list").about("List tags"))
+                .subcommand(
+                    Command::new("create")
+                        .about("Create tag")
+                        .arg(Arg::new("name").help("Tag name").required(true)),
+                )
+                .subcommand(
+                    Command::new("rename")
+                        .about("Rename tag")
+                        .arg(Arg::new("name").help("Tag name").required(true))
+                        .arg(Arg::new("new-name").help("New tag name").required(true)),
+                )
+                .subcommand(
+                    Command::new("rm")
+                        .about("Delete tag, projects in the tag are not affected")
+                        .arg(Arg::new("name").help("Tag name").required(true)),
+                )
+                .subcommand(
+                    Command::new("add")
+                        .about("Add projects to tag")
+                        .arg(Arg::new("name").help("Tag name").required(true))
+                        .arg(
+                            Arg::new("projects")
+                                .help("Names or ids of projects, current project if empty")
+                                .action(ArgAction::Append),
+                        ),
+                )
+                .subcommand(
+                    Command::new("remove")
+                        .about("Remove projects from tag")
+                        .arg(Arg::new("name").help("Tag name").required(true))
+                        .arg(
+                            Arg::new("projects")
+                                .help("Names or ids of projects, current project if empty")
+                                .action(ArgAction::Append),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("watch")
+                .about("Watch local files and push them to remote project on every change")
+                .arg(
+                    Arg::new("debounce")
+                        .long("debounce")
+                        .help(
+                            "How long to wait for further changes before pushing, in milliseconds",
+                        )
+                        .value_parser(value_parser!(u64))
+                        .default_value("500"),
+                ),
+        )
+        .subcommand(
+            Command::new("follow")
+                .about("Apply changes of collaborators to local files as they happen"),
+        )
+        .subcommand(
+            Command::new("daemon")
+                .about("Serve repositories to editor plugins over a local JSON-RPC socket")
+                .arg(
+                    Arg::new("socket")
+                        .long("socket")
+                        .help("Path of the Unix socket [default: $XDG_RUNTIME_DIR/olsyncd.sock]"),
+                ),
+        )
+        .subcommand(
+            Command::new("config")
+                .about("Show or change settings of repository or global ones")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("get")
+                        .about("Print value of setting")
+                        .arg(Arg::new("key").help("Setting, e.g. backup.enabled").required(true)),
+                )
+                .subcommand(
+                    Command::new("set")
+                        .about("Change setting in config of current repository")
+                        .arg(Arg::new("key").help("Setting, e.g. backup.enabled").required(true))
+                        .arg(
+                            Arg::new("value")
+                                .help("Value in TOML syntax, e.g. false or [\"*.log\"], or a plain string")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("global")
+                                .long("global")
+                                .help("Change global config instead")
+                                .action(ArgAction::SetTrue),
+                        ),
+                )
+                .subcommand(Command::new("list").about("Print all settings")),
+        )
+        .get_matches_from(args);
+
+    if matches.get_flag("json") {
+        enable_json_output();
+        colored::control::set_override(false);
+    }
 
-    env_logger::Builder::new()
-        .format(custom_log_format)
-        .filter_level(LevelFilter::Info)
-        .init();
+    if matches.get_flag("yes") {
+        assume_yes();
+    }
+
+    if matches.get_flag("non-interactive") {
+        disable_prompts();
+    }
+
+    if let Err(error) = init_logger(
+        matches.get_count("verbose"),
+        matches.get_flag("quiet"),
+        matches.get_one::<PathBuf>("log-file").map(PathBuf::as_path),
+    ) {
+        eprintln!("{error:#}");
+        std::process::exit(ErrorKind::of(&error).exit_code());
+    }
 
     if let Err(error) = run_olsync(matches).await {
-        error!("{}", error)
+        error!("{}", error);
+
+        if json_output() {
+            print_json_error(&error);
+        }
+
+        std::process::exit(ErrorKind::of(&error).exit_code());
     }
 }
 
+// Wrap error with message saying which action failed. The original error stays in the chain, so
+// that its kind still determines exit code.
+fn failed(message: impl Display, err: anyhow::Error) -> anyhow::Error {
+    let message = format!("{message} with the following error:\n{err}");
+    err.context(message)
+}
+
 async fn run_olsync(matches: ArgMatches) -> Result<()> {
+    // Invalid config must not prevent olsync config from fixing it, so defaults are used instead.
+    let settings = match Settings::load(get_repo_root().ok().as_deref()) {
+        Ok(settings) => settings,
+        Err(err) if matches.subcommand_name() == Some("config") => {
+            warn!("{err:#}\nUsing default settings.");
+            Settings::default()
+        }
+        Err(err) => return Err(err),
+    };
+
+    if !settings.confirm {
+        assume_yes();
+    }
+
+    init_settings(settings);
+
     match matches.subcommand() {
         Some(("whoami", _)) => match whoami_action().await {
             Ok(()) => {}
-            Err(err) => {
-                bail!("Failed to obtain session info with the following error:\n{err}")
-            }
+            Err(err) => return Err(failed("Failed to obtain session info", err)),
         },
         Some(("login", _)) => match login_action().await {
+            Ok((new_session, email)) if json_output() => {
+                print_json(&json!({ "email": email, "new_session": new_session }))
+            }
             Ok((true, email)) => success!("Successfully logged in as {email}."),
             Ok((false, email)) => println!(
                 "Already logged in as {}. Use {} if you want to log into another account.",
                 email.green(),
                 "olsync logout".cyan()
             ),
-            Err(err) => bail!("Failed to log in with the following error:\n{err}"),
+            Err(err) => return Err(failed("Failed to log in", err)),
         },
         Some(("logout", _)) => match logout_action().await {
             Ok(()) => {}
-            Err(err) => bail!("Failed to log out with the following error:\n{err}"),
+            Err(err) => return Err(failed("Failed to log out", err)),
         },
         Some(("clone", matches)) => {
             if is_olsync_repository() {
-                bail!(concat!(
-                    "An Overleaf project has already been cloned in this directory. ",
-                    "Remove the .olsync directory before cloning another project."
+                bail!(OlsyncError::Conflict(
+                    concat!(
+                        "An Overleaf project has already been cloned in this directory. ",
+                        "Remove the .olsync directory before cloning another project."
+                    )
+                    .to_owned()
                 ));
             }
 
             let project_name = matches.get_one::<String>("name");
             let project_id = matches.get_one::<String>("id");
+            let tag = matches.get_one::<String>("tag");
 
-            match clone_action(&mut project_name.cloned(), project_id.cloned()).await {
-                Ok((name, path)) => success!(
+            match clone_action(&mut project_name.cloned(), project_id.cloned(), tag).await {
+                Ok((project, path)) if json_output() => {
+                    print_json(&json!({ "project": project, "path": path }))
+                }
+                Ok((project, path)) => success!(
                     "Successfully cloned project {} into {}.",
-                    name,
+                    project.name,
                     path_to_str(&path)
                 ),
                 Err(err) => {
-                    bail!(
-                        "Failed to clone project {} with the following error:\n{err}",
-                        project_name.unwrap_or_else(|| project_id.unwrap())
-                    )
+                    return Err(failed(
+                        format!(
+                            "Failed to clone project {}",
+                            project_name.or(project_id).map_or("", String::as_str)
+                        ),
+                        err,
+                    ));
                 }
             }
         }
@@ -146,11 +782,33 @@ async fn run_olsync(matches: ArgMatches) -> Result<()> {
             }
 
             let force = matches.get_one::<bool>("force").unwrap_or(&false);
+            let label = matches.get_one::<String>("label");
             let files: Vec<_> = matches.get_many::<String>("files").unwrap().collect();
 
-            match push_action(files, force).await {
-                Ok(true) => success!("Successfully pushed all files!"),
-                Err(err) => bail!("Failed to push some files with the following error:\n{err}"),
+            match push_action(files, force, label.is_some()).await {
+                Ok(Some(pushed)) => {
+                    success!("Successfully pushed all files!");
+
+                    let label = match label {
+                        Some(label) => match label_pushed_version(&pushed, label).await {
+                            Ok(label) => Some(label),
+                            Err(err) => {
+                                return Err(failed(
+                                    format!(
+                                        "Pushed files, but failed to create history label {label}"
+                                    ),
+                                    err,
+                                ))
+                            }
+                        },
+                        None => None,
+                    };
+
+                    if json_output() {
+                        print_json(&json!({ "files": pushed.events, "label": label }));
+                    }
+                }
+                Err(err) => return Err(failed("Failed to push some files", err)),
                 _ => {}
             }
         }
@@ -159,70 +817,461 @@ async fn run_olsync(matches: ArgMatches) -> Result<()> {
                 bail!("Not a olsync repository! Clone a project before pulling.")
             }
 
-            let no_backup = matches.get_one::<bool>("no-backup").unwrap_or(&false);
+            let files: Vec<_> = matches
+                .get_many::<String>("files")
+                .unwrap_or_default()
+                .collect();
+            let no_backup = matches.get_flag("no-backup") || !current_settings().backup.enabled;
             let force = matches.get_one::<bool>("force").unwrap_or(&false);
 
-            match pull_action(no_backup, force).await {
+            match pull_action(files, &no_backup, force).await {
                 Ok(true) => success!("Successfully pulled current project state from Overleaf!"),
-                Err(err) => bail!("Failed to pull the project with the following error:\n{err}"),
+                Err(err) => return Err(failed("Failed to pull the project", err)),
                 _ => {}
             }
         }
-        _ => bail!("Unknown subcommand."),
-    }
+        Some(("compile", matches)) => {
+            if !is_olsync_repository() {
+                bail!("Not a olsync repository! Clone a project before compiling.")
+            }
 
-    Ok(())
-}
+            let output = matches
+                .get_one::<String>("output")
+                .unwrap_or(&current_settings().compile.output);
+            let log = matches.get_one::<bool>("log").unwrap_or(&false);
 
-// Print session info.
-async fn whoami_action() -> Result<()> {
-    if let Some(info) = get_session_info_from_file() {
-        println!("{}", info.email.green());
-        println!(
-            "Session expires at {}",
-            info.session_cookie.expiry_date_pretty()
-        );
-    } else {
-        println!("Not logged in. Use {}.", "olsync login".cyan());
-    }
+            match compile_action(output, log).await {
+                Ok(true) => success!("Successfully compiled project!"),
+                // Result of the compilation has already been printed as the only JSON output.
+                Ok(false) if json_output() => std::process::exit(ErrorKind::Other.exit_code()),
+                Ok(false) if *log => bail!("Compilation of the project failed."),
+                Ok(false) => bail!(
+                    "Compilation of the project failed. Use {} to see errors.",
+                    "olsync compile --log".cyan()
+                ),
+                Err(err) => return Err(failed("Failed to compile the project", err)),
+            }
+        }
+        Some(("output", matches)) => {
+            if !is_olsync_repository() {
+                bail!("Not a olsync repository! Clone a project before downloading output files.")
+            }
 
-    Ok(())
-}
+            let dir = matches.get_one::<String>("dir").unwrap();
+            let files: Vec<_> = matches
+                .get_many::<String>("files")
+                .unwrap_or_default()
+                .collect();
 
-// Log in if currently logged out and return user email.
-async fn login_action() -> Result<(bool, String)> {
-    if let Some(info) = get_session_info_from_file() {
-        Ok((false, info.email))
-    } else {
-        let session_info = get_session_info_from_browser().await?;
-        Ok((true, session_info.email))
-    }
-}
+            match output_action(files, dir).await {
+                Ok(true) => success!("Successfully downloaded output files!"),
+                Err(err) => return Err(failed("Failed to download output files", err)),
+                _ => {}
+            }
+        }
+        Some(("settings", matches)) => {
+            if !is_olsync_repository() {
+                bail!("Not a olsync repository! Clone a project before changing its settings.")
+            }
 
-// Log out if currently logged in.
-async fn logout_action() -> Result<()> {
-    if let Some(info) = get_session_info_from_file() {
-        remove_session_info()?;
-        println!("Logged out from {}", info.email.green());
-    } else {
-        println!("Already logged out.")
-    }
+            let settings_update = ProjectSettingsUpdate {
+                name: matches.get_one::<String>("name").cloned(),
+                compiler: matches.get_one::<String>("compiler").cloned(),
+                image_name: matches.get_one::<String>("texlive").cloned(),
+                root_doc_id: None,
+                spell_check_language: matches.get_one::<String>("spell-check").cloned(),
+            };
+            let main_document = matches.get_one::<String>("main-document");
 
-    Ok(())
-}
+            match settings_action(settings_update, main_document).await {
+                Ok(true) => success!("Successfully updated project settings!"),
+                Err(err) => return Err(failed("Failed to update project settings", err)),
+                _ => {}
+            }
+        }
+        Some(("log", matches)) => {
+            if !is_olsync_repository() {
+                bail!("Not a olsync repository! Clone a project before viewing its history.")
+            }
 
-// Clone project into ./{project_name} directory and return (project_name, project_path).
-async fn clone_action(
-    project_name: &mut Option<String>,
+            let limit = matches.get_one::<u32>("limit").unwrap();
+            let before = matches.get_one::<i64>("before");
+            let since_pull = matches.get_one::<bool>("since-pull").unwrap_or(&false);
+            let labels = matches.get_one::<bool>("labels").unwrap_or(&false);
+
+            if let Err(err) = log_action(limit, before.copied(), since_pull, labels).await {
+                return Err(failed("Failed to fetch project history", err));
+            }
+        }
+        Some(("checkout", matches)) => {
+            if !is_olsync_repository() {
+                bail!("Not a olsync repository! Clone a project before checking out its versions.")
+            }
+
+            let files: Vec<_> = matches
+                .get_many::<String>("files")
+                .unwrap_or_default()
+                .collect();
+            let version = matches.get_one::<u64>("version");
+            let label = matches.get_one::<String>("label");
+            let into = matches.get_one::<String>("into");
+            let no_backup = matches.get_flag("no-backup") || !current_settings().backup.enabled;
+            let force = matches.get_one::<bool>("force").unwrap_or(&false);
+
+            match checkout_action(files, version, label, into, &no_backup, force).await {
+                Ok(true) => success!("Successfully checked out past version of the project!"),
+                Err(err) => {
+                    return Err(failed(
+                        "Failed to check out past version of the project",
+                        err,
+                    ))
+                }
+                _ => {}
+            }
+        }
+        Some(("restore-file", matches)) => {
+            if !is_olsync_repository() {
+                bail!("Not a olsync repository! Clone a project before restoring its files.")
+            }
+
+            let path = matches.get_one::<String>("path").unwrap();
+            let version = matches.get_one::<u64>("version");
+            let label = matches.get_one::<String>("label");
+            let force = matches.get_one::<bool>("force").unwrap_or(&false);
+
+            match restore_file_action(path, version, label, force).await {
+                Ok(true) => success!("Successfully restored file {path}!"),
+                Err(err) => return Err(failed(format!("Failed to restore file {path}"), err)),
+                _ => {}
+            }
+        }
+        Some(("label", matches)) => {
+            if !is_olsync_repository() {
+                bail!("Not a olsync repository! Clone a project before managing its labels.")
+            }
+
+            match matches.subcommand() {
+                Some(("add", matches)) => {
+                    let name = matches.get_one::<String>("name").unwrap();
+                    let version = matches.get_one::<u64>("version");
+
+                    match label_add_action(name, version).await {
+                        Ok(label) => {
+                            success!("Successfully labeled version {} as {name}!", label.version);
+
+                            if json_output() {
+                                print_json(&json!({ "label": label }));
+                            }
+                        }
+                        Err(err) => return Err(failed("Failed to create history label", err)),
+                    }
+                }
+                Some(("rm", matches)) => {
+                    let name = matches.get_one::<String>("name").unwrap();
+                    let version = matches.get_one::<u64>("version");
+
+                    match label_rm_action(name, version).await {
+                        Ok(label) => {
+                            success!("Successfully deleted history label {name}!");
+
+                            if json_output() {
+                                print_json(&json!({ "removed": label }));
+                            }
+                        }
+                        Err(err) => return Err(failed("Failed to delete history label", err)),
+                    }
+                }
+                _ => bail!("Unknown subcommand."),
+            }
+        }
+        Some(("share", matches)) => {
+            if !is_olsync_repository() {
+                bail!("Not a olsync repository! Clone a project before managing its sharing.")
+            }
+
+            let result = match matches.subcommand() {
+                Some(("list", _)) => share_list_action().await,
+                Some(("invite", matches)) => {
+                    let emails: Vec<_> = matches.get_many::<String>("emails").unwrap().collect();
+                    let permission = matches.get_one::<String>("permission").unwrap();
+
+                    share_invite_action(emails, permission).await
+                }
+                Some(("set", matches)) => {
+                    let email = matches.get_one::<String>("email").unwrap();
+                    let permission = matches.get_one::<String>("permission").unwrap();
+
+                    share_set_action(email, permission).await
+                }
+                Some(("rm", matches)) => {
+                    let email = matches.get_one::<String>("email").unwrap();
+
+                    share_rm_action(email).await
+                }
+                Some(("transfer", matches)) => {
+                    let email = matches.get_one::<String>("email").unwrap();
+                    let force = matches.get_one::<bool>("force").unwrap_or(&false);
+
+                    share_transfer_action(email, force).await
+                }
+                Some(("link", matches)) => {
+                    let enabled = matches.get_one::<String>("state").unwrap() == "on";
+
+                    share_link_action(enabled).await
+                }
+                _ => bail!("Unknown subcommand."),
+            };
+
+            if let Err(err) = result {
+                return Err(failed("Failed to manage project sharing", err));
+            }
+        }
+        Some(("batch", matches)) => {
+            let operation =
+                BatchOperation::from_name(matches.get_one::<String>("operation").unwrap())
+                    .context("Unknown batch operation.")?;
+
+            let filter = ProjectFilter {
+                tag: matches.get_one::<String>("tag").cloned(),
+                name_pattern: matches.get_one::<String>("name").cloned(),
+                ids: match matches.get_one::<String>("ids-file") {
+                    Some(path) => Some(read_ids_file(&PathBuf::from(path))?),
+                    None => None,
+                },
+            };
+            let dir = matches.get_one::<String>("dir").unwrap();
+            let jobs = matches.get_one::<usize>("jobs").unwrap();
+            let no_backup = matches.get_one::<bool>("no-backup").unwrap_or(&false);
+
+            match batch_action(operation, filter, dir, jobs, no_backup).await {
+                Ok(count) => success!("Successfully processed {count} projects!"),
+                Err(err) => return Err(failed("Batch operation failed", err)),
+            }
+        }
+        Some(("backup-all", matches)) => {
+            let dir = matches.get_one::<String>("dir").unwrap();
+
+            match backup_all_action(dir).await {
+                Ok((downloaded, skipped)) if json_output() => {
+                    print_json(&json!({ "downloaded": downloaded, "skipped": skipped }))
+                }
+                Ok((downloaded, skipped)) => success!(
+                    "Successfully archived {downloaded} projects, {skipped} unchanged projects skipped."
+                ),
+                Err(err) => return Err(failed("Failed to archive projects", err)),
+            }
+        }
+        Some(("list", matches)) => {
+            let filter = ProjectFilter {
+                tag: matches.get_one::<String>("tag").cloned(),
+                name_pattern: matches.get_one::<String>("name").cloned(),
+                ids: None,
+            };
+
+            if let Err(err) = list_action(filter).await {
+                return Err(failed("Failed to list projects", err));
+            }
+        }
+        Some(("tag", matches)) => {
+            let result = match matches.subcommand() {
+                Some(("list", _)) => tag_list_action().await,
+                Some(("create", matches)) => {
+                    tag_create_action(matches.get_one::<String>("name").unwrap()).await
+                }
+                Some(("rename", matches)) => {
+                    let name = matches.get_one::<String>("name").unwrap();
+                    let new_name = matches.get_one::<String>("new-name").unwrap();
+
+                    tag_rename_action(name, new_name).await
+                }
+                Some(("rm", matches)) => {
+                    tag_rm_action(matches.get_one::<String>("name").unwrap()).await
+                }
+                Some((subcommand @ ("add" | "remove"), matches)) => {
+                    let name = matches.get_one::<String>("name").unwrap();
+                    let projects: Vec<_> = matches
+                        .get_many::<String>("projects")
+                        .unwrap_or_default()
+                        .collect();
+
+                    tag_projects_action(name, projects, subcommand == "add").await
+                }
+                _ => bail!("Unknown subcommand."),
+            };
+
+            if let Err(err) = result {
+                return Err(failed("Failed to manage tags", err));
+            }
+        }
+        Some(("watch", matches)) => {
+            if !is_olsync_repository() {
+                bail!("Not a olsync repository! Clone a project before watching.")
+            }
+
+            let debounce = matches.get_one::<u64>("debounce").unwrap();
+
+            if let Err(err) = watch_action(debounce).await {
+                return Err(failed("Failed to watch the project", err));
+            }
+        }
+        Some(("follow", _)) => {
+            if !is_olsync_repository() {
+                bail!("Not a olsync repository! Clone a project before following.")
+            }
+
+            if let Err(err) = follow_action().await {
+                return Err(failed("Failed to follow the project", err));
+            }
+        }
+        Some(("daemon", matches)) => {
+            let socket = matches.get_one::<String>("socket");
+
+            if let Err(err) = daemon_action(socket).await {
+                return Err(failed("Daemon failed", err));
+            }
+        }
+        Some(("backup", matches)) => {
+            if !is_olsync_repository() {
+                bail!("Not a olsync repository! Clone a project before managing its backups.")
+            }
+
+            let result = match matches.subcommand() {
+                Some(("list", _)) => backup_list_action(),
+                Some(("restore", matches)) => {
+                    let id = matches.get_one::<String>("id").unwrap();
+                    let force = matches.get_flag("force");
+
+                    backup_restore_action(id, force)
+                }
+                Some(("prune", matches)) => {
+                    let keep = matches.get_one::<usize>("keep").copied();
+                    let older_than = matches.get_one::<String>("older-than");
+                    let force = matches.get_flag("force");
+
+                    backup_prune_action(keep, older_than, force)
+                }
+                _ => bail!("Unknown subcommand."),
+            };
+
+            if let Err(err) = result {
+                return Err(failed("Failed to manage backups", err));
+            }
+        }
+        Some(("config", matches)) => {
+            let result = match matches.subcommand() {
+                Some(("get", matches)) => {
+                    config_get_action(matches.get_one::<String>("key").unwrap())
+                }
+                Some(("set", matches)) => {
+                    let key = matches.get_one::<String>("key").unwrap();
+                    let value = matches.get_one::<String>("value").unwrap();
+                    let global = matches.get_flag("global");
+
+                    config_set_action(key, value, global)
+                }
+                Some(("list", _)) => config_list_action(),
+                _ => bail!("Unknown subcommand."),
+            };
+
+            if let Err(err) = result {
+                return Err(failed("Failed to manage config", err));
+            }
+        }
+        _ => bail!("Unknown subcommand."),
+    }
+
+    Ok(())
+}
+
+// Read cached session info. Browser for logging in is opened only in interactive mode, otherwise
+// missing session is an error.
+async fn session_info() -> Result<SessionInfo> {
+    if is_interactive() {
+        get_session_info(&SpinnerProgress::default()).await
+    } else {
+        get_session_info_from_file().ok_or(anyhow!(OlsyncError::AuthExpired))
+    }
+}
+
+// Print session info.
+async fn whoami_action() -> Result<()> {
+    if json_output() {
+        let info = get_session_info_from_file();
+
+        print_json(&json!({
+            "logged_in": info.is_some(),
+            "email": info.as_ref().map(|info| &info.email),
+            "expires_at": info.as_ref().and_then(|info| {
+                chrono::DateTime::from_timestamp(info.session_cookie.expires as i64, 0)
+                    .map(|date| date.to_rfc3339())
+            }),
+        }));
+
+        return Ok(());
+    }
+
+    if let Some(info) = get_session_info_from_file() {
+        println!("{}", info.email.green());
+        println!(
+            "Session expires at {}",
+            info.session_cookie.expiry_date_pretty()
+        );
+    } else {
+        println!("Not logged in. Use {}.", "olsync login".cyan());
+    }
+
+    Ok(())
+}
+
+// Log in if currently logged out and return user email.
+async fn login_action() -> Result<(bool, String)> {
+    if let Some(info) = get_session_info_from_file() {
+        Ok((false, info.email))
+    } else if !is_interactive() {
+        bail!("Logging in requires a browser and cannot be done in non-interactive mode.")
+    } else {
+        let session_info = get_session_info_from_browser(&SpinnerProgress::default()).await?;
+        Ok((true, session_info.email))
+    }
+}
+
+// Log out if currently logged in.
+async fn logout_action() -> Result<()> {
+    let info = get_session_info_from_file();
+
+    if info.is_some() {
+        remove_session_info()?;
+    }
+
+    if json_output() {
+        print_json(&json!({ "logged_out": info.map(|info| info.email) }));
+    } else if let Some(info) = info {
+        println!("Logged out from {}", info.email.green());
+    } else {
+        println!("Already logged out.")
+    }
+
+    Ok(())
+}
+
+// Clone project into ./{project_name} directory and return (project, project_path).
+async fn clone_action(
+    project_name: &mut Option<String>,
     project_id: Option<String>,
-) -> Result<(String, PathBuf)> {
-    let session_info = get_session_info().await?;
-    let overleaf_client = OverleafClient::new(session_info)?;
+    tag: Option<&String>,
+) -> Result<(Project, PathBuf)> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
 
     if project_name.is_none() && project_id.is_none() {
         let mut spinner = OlSpinner::new("Fetching list of projects...".to_owned());
 
-        let projects_list_result = overleaf_client.get_all_projects().await;
+        let filter = ProjectFilter {
+            tag: tag.cloned(),
+            ..Default::default()
+        };
+
+        let projects_list_result = select_projects(&overleaf_client, &filter).await;
 
         if projects_list_result.is_err() {
             spinner.stop_with_error("Failed to fetch list of projects.".to_owned());
@@ -233,13 +1282,15 @@ async fn clone_action(
 
         let projects_list = projects_list_result
             .unwrap()
-            .projects
             .into_iter()
             .map(|project| project.name)
             .collect();
 
-        let selected_project_name =
-            inquire::Select::new("Select project to clone.", projects_list).prompt()?;
+        let selected_project_name = select(
+            "Select project to clone.",
+            projects_list,
+            "Specify the project with --name or --id.",
+        )?;
 
         project_name.replace(selected_project_name);
     }
@@ -264,58 +1315,1473 @@ async fn clone_action(
 
     spinner.stop_with_success(format!("Fetched information for project {}.", project.name));
 
-    let repo_root = init_olsync_repository(&project)?;
+    let repo_root = init_olsync_repository(&project, &env::current_dir()?.join(&project.name))?;
+
+    download_project(
+        &overleaf_client,
+        &project.id,
+        &repo_root,
+        None,
+        &SpinnerProgress::default(),
+    )
+    .await?;
+
+    save_last_pull_timestamp(&repo_root)?;
 
-    download_project(&overleaf_client, &project.id, &repo_root, None).await?;
+    Ok((project, repo_root))
+}
 
-    Ok((project.name, repo_root))
+// Project pushed by push action together with outcomes of pushed files, with its latest version
+// from before the push if the pushed version is going to be labeled.
+struct PushedProject {
+    overleaf_client: OverleafClient,
+    project_id: String,
+    previous_version: Option<u64>,
+    events: Vec<SyncEvent>,
 }
 
-// Push files to remote. Currently only files in root project directory are supported.
-async fn push_action(files: Vec<&String>, force: &bool) -> Result<bool> {
-    let confirm = inquire::Confirm::new(
+// Push files to remote. Returns None if user declined. Currently only files in root project
+// directory are supported.
+async fn push_action(
+    files: Vec<&String>,
+    force: &bool,
+    label: bool,
+) -> Result<Option<PushedProject>> {
+    let ans = confirm(
         "Pushing files to Overleaf will override them. Do you want to continue?",
+        None,
+        *force,
+    )?;
+
+    if !ans {
+        return Ok(None);
+    }
+
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let project = get_project_info()?;
+
+    let previous_version = if label {
+        overleaf_client.get_latest_version(&project.id).await?
+    } else {
+        None
+    };
+
+    let events = push_files(
+        &overleaf_client,
+        &project.id,
+        &get_repo_root()?,
+        files,
+        &SpinnerProgress::default(),
+    )
+    .await?;
+
+    Ok(Some(PushedProject {
+        overleaf_client,
+        project_id: project.id,
+        previous_version,
+        events,
+    }))
+}
+
+// Create history label on the version created by push.
+async fn label_pushed_version(pushed: &PushedProject, label: &str) -> Result<Label> {
+    create_label_on_latest_version(
+        &pushed.overleaf_client,
+        &pushed.project_id,
+        label,
+        pushed.previous_version,
+        &SpinnerProgress::default(),
     )
-    .with_default(false);
+    .await
+}
 
-    let ans = if *force { Ok(true) } else { confirm.prompt() };
+// Pull the current state from remote, either of the whole project or only of given files.
+async fn pull_action(files: Vec<&String>, no_backup: &bool, force: &bool) -> Result<bool> {
+    let message = if files.is_empty() {
+        "Pulling project from Overleaf will override your local state. Do you want to continue?"
+    } else {
+        "Pulling files from Overleaf will override their local state. Do you want to continue?"
+    };
 
-    if matches!(ans, Ok(true)) {
-        let session_info = get_session_info().await?;
-        let overleaf_client = OverleafClient::new(session_info)?;
+    let ans = confirm(
+        message,
+        Some("If you proceed, your local project will be backed up (unless backups are disabled with --no-backup or in config)."),
+        *force,
+    )?;
+
+    if ans {
+        let session_info = session_info().await?;
+        let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
 
         let project = get_project_info()?;
+        let repo_root = get_repo_root()?;
 
-        push_files(&overleaf_client, &project.id, files).await?;
+        if !no_backup {
+            create_local_backup(&repo_root, &SpinnerProgress::default())?;
+        }
+
+        if !files.is_empty() {
+            let events = pull_files(
+                &overleaf_client,
+                &project.id,
+                &repo_root,
+                files,
+                &SpinnerProgress::default(),
+            )
+            .await?;
+
+            if json_output() {
+                print_json(&json!({ "files": events }));
+            }
+
+            return Ok(true);
+        }
+
+        pull_project(
+            &overleaf_client,
+            &project.id,
+            &repo_root,
+            &SpinnerProgress::default(),
+        )
+        .await?;
+
+        if json_output() {
+            print_json(&json!({ "project": project, "path": repo_root }));
+        }
     }
 
-    ans.map_err(|e| anyhow!("An error ocurred in prompt: {e}"))
+    Ok(ans)
 }
 
-// Pull the current state from remote.
-async fn pull_action(no_backup: &bool, force: &bool) -> Result<bool> {
-    let confirm = inquire::Confirm::new(
-        "Pulling project from Overleaf will override your local state. Do you want to continue?")
-        .with_help_message("If you proceed, your local project will be backed up (unless --no-backup option has been used).")
-        .with_default(false);
+// Compile project on Overleaf, download the PDF and optionally print diagnostics from compile
+// log. Returns whether the compilation succeeded.
+async fn compile_action(output: &String, log: &bool) -> Result<bool> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let project = get_project_info()?;
+    let repo_root = get_repo_root()?;
 
-    let ans = if *force { Ok(true) } else { confirm.prompt() };
+    let compile_result = compile_project(
+        &overleaf_client,
+        &project.id,
+        &repo_root,
+        &SpinnerProgress::default(),
+    )
+    .await?;
 
-    if matches!(ans, Ok(true)) {
-        let session_info = get_session_info().await?;
-        let overleaf_client = OverleafClient::new(session_info)?;
+    save_last_compile(&repo_root, &compile_result)?;
 
-        if !no_backup {
-            create_local_backup()?;
+    let pdf_path = if compile_result.output_file("output.pdf").is_some() {
+        download_output_file(
+            &overleaf_client,
+            &compile_result,
+            "output.pdf",
+            &repo_root.join(output),
+            &SpinnerProgress::default(),
+        )
+        .await?;
+
+        save_compile_output_path(&repo_root, output)?;
+
+        Some(repo_root.join(output))
+    } else {
+        None
+    };
+
+    let diagnostics = if *log {
+        let compile_log = fetch_compile_log(&overleaf_client, &compile_result).await?;
+
+        let mut diagnostics = parse_latex_log(&compile_log);
+        resolve_local_paths(&mut diagnostics, &repo_root);
+
+        Some(diagnostics)
+    } else {
+        None
+    };
+
+    if json_output() {
+        print_json(&json!({
+            "compiled": compile_result.is_success(),
+            "pdf": pdf_path,
+            "diagnostics": diagnostics,
+        }));
+    } else {
+        for diagnostic in diagnostics.unwrap_or_default() {
+            println!("{diagnostic}");
         }
+    }
 
-        let project = get_project_info()?;
-        let repo_root = get_repo_root()?;
+    Ok(compile_result.is_success())
+}
+
+// Download output files of the last remote compile into directory relative to project root.
+// If no files are specified, list available output files instead and return false.
+async fn output_action(files: Vec<&String>, dir: &String) -> Result<bool> {
+    let compile_result = get_last_compile()?;
+
+    if files.is_empty() {
+        if json_output() {
+            print_json(&json!({ "output_files": compile_result.output_files }));
+            return Ok(false);
+        }
+
+        println!("Output files of the last compile:");
+
+        for output_file in &compile_result.output_files {
+            println!("  {}", output_file.path.cyan());
+        }
+
+        return Ok(false);
+    }
+
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let target_dir = get_repo_root()?.join(dir);
+    fs::create_dir_all(&target_dir)?;
+
+    let mut downloaded = Vec::new();
+
+    for file in files {
+        let output_path = compile_result
+            .output_file(file)
+            .map(|output_file| output_file.path.clone())
+            .ok_or(anyhow!(
+                "Output file {file} not found in the last compile. Use {} to list available files.",
+                "olsync output".cyan()
+            ))?;
+
+        download_output_file(
+            &overleaf_client,
+            &compile_result,
+            &output_path,
+            &target_dir.join(&output_path),
+            &SpinnerProgress::default(),
+        )
+        .await?;
+
+        downloaded.push(target_dir.join(&output_path));
+    }
+
+    if json_output() {
+        print_json(&json!({ "downloaded": downloaded }));
+    }
 
-        wipe_project()?;
+    Ok(true)
+}
+
+// Apply settings changes to remote project, print current settings and save them in .olsync
+// directory. Returns whether any settings were changed.
+async fn settings_action(
+    mut settings_update: ProjectSettingsUpdate,
+    main_document: Option<&String>,
+) -> Result<bool> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let mut project = get_project_info()?;
+    let project_details =
+        fetch_project_details(&overleaf_client, &project.id, &SpinnerProgress::default())?;
+
+    let mut settings = ProjectSettings::from_details(&project_details);
+
+    if let Some(path) = main_document {
+        let remote_path = current_settings().remote_path(path.trim_start_matches("./"));
+
+        let root_doc = project_details
+            .root_folder
+            .first()
+            .and_then(|root_folder| root_folder.find_doc(&remote_path))
+            .context(format!("Document {path} not found in remote project."))?;
+
+        settings_update.root_doc_id = Some(root_doc.id.clone());
+        settings.root_doc_path = Some(remote_path);
+    }
+
+    let updated = !settings_update.is_empty();
+
+    if updated {
+        let mut spinner = OlSpinner::new("Updating project settings...".to_owned());
+
+        if let Err(err) = overleaf_client
+            .update_project_settings(&project.id, &settings_update)
+            .await
+        {
+            spinner.stop_with_error("Failed to update project settings.".to_owned());
+            return Err(err);
+        }
+
+        spinner.stop_with_success("Updated project settings.".to_owned());
+
+        settings.name = settings_update.name.unwrap_or(settings.name);
+        settings.compiler = settings_update.compiler.or(settings.compiler);
+        settings.image_name = settings_update.image_name.or(settings.image_name);
+        settings.spell_check_language = settings_update
+            .spell_check_language
+            .or(settings.spell_check_language);
+    }
+
+    save_project_settings(&settings)?;
 
-        download_project(&overleaf_client, &project.id, &repo_root, None).await?;
+    if project.name != settings.name {
+        project.name = settings.name.clone();
+        save_project_info(&project)?;
     }
 
-    ans.map_err(|e| anyhow!("An error ocurred in prompt: {e}"))
+    if json_output() {
+        print_json(&json!({ "settings": settings }));
+        return Ok(updated);
+    }
+
+    let not_set = || "not set".to_owned();
+
+    println!("{:<18}{}", "Name:", settings.name.green());
+    println!(
+        "{:<18}{}",
+        "Compiler:",
+        settings.compiler.unwrap_or_else(not_set).cyan()
+    );
+    println!(
+        "{:<18}{}",
+        "TeX Live image:",
+        settings.image_name.unwrap_or_else(not_set).cyan()
+    );
+    println!(
+        "{:<18}{}",
+        "Main document:",
+        settings.root_doc_path.unwrap_or_else(not_set).cyan()
+    );
+    println!(
+        "{:<18}{}",
+        "Spell check:",
+        settings.spell_check_language.unwrap_or_else(not_set).cyan()
+    );
+
+    Ok(updated)
+}
+
+// Format timestamp in milliseconds as local date and time.
+fn format_timestamp(timestamp: i64) -> String {
+    Local
+        .timestamp_millis_opt(timestamp)
+        .single()
+        .map(|date| date.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or(timestamp.to_string())
+}
+
+// Print single history update with its authors, touched files and labels.
+fn print_history_update(update: &HistoryUpdate) {
+    let authors = update
+        .meta
+        .users
+        .iter()
+        .map(|user| match user {
+            Some(user) => user.display_name(),
+            None => "Anonymous".to_owned(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    println!(
+        "{}  {}  {}",
+        format!("v{}..v{}", update.from_version, update.to_version).yellow(),
+        format_timestamp(update.meta.end_ts),
+        authors.green()
+    );
+
+    for pathname in &update.pathnames {
+        println!("    edited   {pathname}");
+    }
+
+    for op in &update.project_ops {
+        if let Some(add) = &op.add {
+            println!("    added    {}", add.pathname);
+        }
+        if let Some(rename) = &op.rename {
+            println!(
+                "    renamed  {} -> {}",
+                rename.pathname,
+                rename.new_pathname.as_deref().unwrap_or("?")
+            );
+        }
+        if let Some(remove) = &op.remove {
+            println!("    removed  {}", remove.pathname);
+        }
+    }
+
+    for label in &update.labels {
+        println!("    {} {}", "label".cyan(), label.comment.cyan());
+    }
+}
+
+// Print history updates of the project, most recent first, or its history labels.
+async fn log_action(
+    limit: &u32,
+    before: Option<i64>,
+    since_pull: &bool,
+    labels: &bool,
+) -> Result<()> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let project = get_project_info()?;
+
+    if *labels {
+        let mut labels = overleaf_client.get_labels(&project.id).await?;
+        labels.sort_by_key(|label| std::cmp::Reverse(label.version));
+
+        if json_output() {
+            print_json(&json!({ "labels": labels }));
+            return Ok(());
+        }
+
+        if labels.is_empty() {
+            println!("Project has no history labels.");
+        }
+
+        for label in labels {
+            println!(
+                "{}  {}  {}",
+                format!("v{}", label.version).yellow(),
+                label.comment.cyan(),
+                label.user_display_name.unwrap_or_default().green()
+            );
+        }
+
+        return Ok(());
+    }
+
+    let since = if *since_pull {
+        Some(get_last_pull_timestamp()?)
+    } else {
+        None
+    };
+
+    let reached_since = |updates: &[HistoryUpdate]| {
+        since.is_some_and(|since| {
+            updates
+                .last()
+                .is_some_and(|update| update.meta.end_ts < since)
+        })
+    };
+
+    let mut spinner = OlSpinner::new("Fetching project history...".to_owned());
+
+    let mut updates: Vec<HistoryUpdate> = Vec::new();
+    let mut next_before = before;
+
+    loop {
+        let page = match overleaf_client
+            .get_history_updates(&project.id, next_before, *limit)
+            .await
+        {
+            Ok(page) => page,
+            Err(err) => {
+                spinner.stop_with_error("Failed to fetch project history.".to_owned());
+                return Err(err);
+            }
+        };
+
+        updates.extend(page.updates);
+        next_before = page.next_before_timestamp;
+
+        if updates.len() >= *limit as usize || next_before.is_none() || reached_since(&updates) {
+            break;
+        }
+    }
+
+    spinner.stop_with_success("Fetched project history.".to_owned());
+
+    // Older pages are not needed once updates from before the pull were reached, but when
+    // fetching stopped at the limit, more updates since the pull may follow.
+    if let Some(since) = since {
+        if reached_since(&updates) {
+            next_before = None;
+        }
+
+        updates.retain(|update| update.meta.end_ts >= since);
+    }
+
+    if updates.len() > *limit as usize {
+        updates.truncate(*limit as usize);
+        next_before = updates.last().map(|update| update.meta.start_ts);
+    }
+
+    if json_output() {
+        print_json(&json!({ "updates": updates, "next_before": next_before }));
+        return Ok(());
+    }
+
+    if updates.is_empty() {
+        println!("No updates found.");
+    }
+
+    for update in &updates {
+        print_history_update(update);
+    }
+
+    // Filters of this call are repeated, so that the next page shows the same kind of updates.
+    if let Some(next_before) = next_before {
+        let mut next_page = format!("olsync log --limit {limit} --before {next_before}");
+
+        if *since_pull {
+            next_page.push_str(" --since-pull");
+        }
+
+        println!(
+            "\nMore updates available. Use {} to see them.",
+            next_page.cyan()
+        );
+    }
+
+    Ok(())
+}
+
+// Get history version either given explicitly or by label name.
+async fn resolve_version(
+    overleaf_client: &OverleafClient,
+    project_id: &str,
+    version: Option<&u64>,
+    label: Option<&String>,
+) -> Result<u64> {
+    if let Some(version) = version {
+        return Ok(*version);
+    }
+
+    let label = label.context("Neither version nor label has been specified.")?;
+
+    overleaf_client
+        .get_labels(project_id)
+        .await?
+        .into_iter()
+        .filter(|l| l.comment == *label)
+        .max_by_key(|l| l.version)
+        .map(|l| l.version)
+        .context(format!("History label {label} not found."))
+}
+
+// Download project or specified files as of past version, either into separate directory
+// or overriding the local state.
+async fn checkout_action(
+    files: Vec<&String>,
+    version: Option<&u64>,
+    label: Option<&String>,
+    into: Option<&String>,
+    no_backup: &bool,
+    force: &bool,
+) -> Result<bool> {
+    let ans = confirm(
+        "Checking out a past version will override your local state. Do you want to continue?",
+        Some("If you proceed, your local project will be backed up (unless backups are disabled with --no-backup or in config)."),
+        *force || into.is_some(),
+    )?;
+
+    if ans {
+        let session_info = session_info().await?;
+        let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+        let project = get_project_info()?;
+        let version = resolve_version(&overleaf_client, &project.id, version, label).await?;
+
+        let target_dir = match into {
+            Some(dir) => {
+                let target_dir = PathBuf::from(dir);
+                fs::create_dir_all(&target_dir)?;
+
+                download_project_version(
+                    &overleaf_client,
+                    &project.id,
+                    version,
+                    &target_dir,
+                    &files,
+                    current_settings(),
+                    &SpinnerProgress::default(),
+                )
+                .await?;
+
+                target_dir
+            }
+            None => {
+                let repo_root = get_repo_root()?;
+
+                if !no_backup {
+                    create_local_backup(&repo_root, &SpinnerProgress::default())?;
+                }
+
+                if files.is_empty() {
+                    wipe_project(&repo_root)?;
+                }
+
+                download_project_version(
+                    &overleaf_client,
+                    &project.id,
+                    version,
+                    &repo_root,
+                    &files,
+                    current_settings(),
+                    &SpinnerProgress::default(),
+                )
+                .await?;
+
+                repo_root
+            }
+        };
+
+        if json_output() {
+            print_json(&json!({ "version": version, "files": files, "path": target_dir }));
+        }
+    }
+
+    Ok(ans)
+}
+
+// Restore file in remote project to its state from past version.
+async fn restore_file_action(
+    path: &String,
+    version: Option<&u64>,
+    label: Option<&String>,
+    force: &bool,
+) -> Result<bool> {
+    let ans = confirm(
+        "Restoring file will override its current state on Overleaf. Do you want to continue?",
+        None,
+        *force,
+    )?;
+
+    if ans {
+        let session_info = session_info().await?;
+        let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+        let project = get_project_info()?;
+        let version = resolve_version(&overleaf_client, &project.id, version, label).await?;
+
+        let mut spinner = OlSpinner::new(format!("Restoring {path} to version {version}..."));
+
+        let remote_path = current_settings().remote_path(path.trim_start_matches("./"));
+
+        if let Err(err) = overleaf_client
+            .restore_file(&project.id, version, &remote_path)
+            .await
+        {
+            spinner.stop_with_error(format!("Failed to restore {path}."));
+            return Err(err);
+        }
+
+        spinner.stop_with_success(format!("Restored {path} to version {version}."));
+
+        if json_output() {
+            print_json(&json!({ "restored": path, "version": version }));
+        }
+    }
+
+    Ok(ans)
+}
+
+// Create history label on given or the current version of remote project and return it.
+async fn label_add_action(name: &str, version: Option<&u64>) -> Result<Label> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let project = get_project_info()?;
+
+    let label = match version {
+        Some(version) => {
+            let mut spinner = OlSpinner::new(format!("Creating history label {name}..."));
+
+            match overleaf_client
+                .create_label(&project.id, name, *version)
+                .await
+            {
+                Ok(label) => {
+                    spinner.stop_with_success(format!("Created history label {name}."));
+                    label
+                }
+                Err(err) => {
+                    spinner.stop_with_error(format!("Failed to create history label {name}."));
+                    return Err(err);
+                }
+            }
+        }
+        None => {
+            create_label_on_latest_version(
+                &overleaf_client,
+                &project.id,
+                name,
+                None,
+                &SpinnerProgress::default(),
+            )
+            .await?
+        }
+    };
+
+    Ok(label)
+}
+
+// Delete history label with given name and return it.
+async fn label_rm_action(name: &String, version: Option<&u64>) -> Result<Label> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let project = get_project_info()?;
+
+    let labels: Vec<_> = overleaf_client
+        .get_labels(&project.id)
+        .await?
+        .into_iter()
+        .filter(|label| label.comment == *name)
+        .filter(|label| version.is_none_or(|version| label.version == *version))
+        .collect();
+
+    let label = match labels.as_slice() {
+        [] => bail!("History label {name} not found."),
+        [label] => label,
+        _ => bail!(
+            "There are several history labels named {name}. Use {} to choose one.",
+            "--version".cyan()
+        ),
+    };
+
+    overleaf_client.delete_label(&project.id, &label.id).await?;
+
+    Ok(label.clone())
+}
+
+// Map permission accepted by share subcommands to Overleaf privilege level.
+fn permission_to_privileges(permission: &str) -> &'static str {
+    match permission {
+        "read-only" => "readOnly",
+        "review" => "review",
+        _ => "readAndWrite",
+    }
+}
+
+// Map Overleaf privilege level to permission accepted by share subcommands.
+fn privileges_to_permission(privileges: &str) -> &str {
+    match privileges {
+        "readOnly" => "read-only",
+        "readAndWrite" => "read-write",
+        other => other,
+    }
+}
+
+// Find id of project member with given email.
+async fn find_member_id(
+    overleaf_client: &OverleafClient,
+    project_id: &str,
+    email: &String,
+) -> Result<String> {
+    overleaf_client
+        .get_project_members(project_id)
+        .await?
+        .into_iter()
+        .find(|member| member.email.eq_ignore_ascii_case(email))
+        .map(|member| member.id)
+        .context(format!("User {email} is not a member of the project."))
+}
+
+// Print members of the project and pending invites.
+async fn share_list_action() -> Result<()> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let project = get_project_info()?;
+
+    let members = overleaf_client.get_project_members(&project.id).await?;
+    let invites = overleaf_client.get_project_invites(&project.id).await?;
+
+    if json_output() {
+        let members: Vec<_> = members
+            .into_iter()
+            .map(|member| {
+                json!({
+                    "id": member.id,
+                    "email": member.email,
+                    "first_name": member.first_name,
+                    "last_name": member.last_name,
+                    "permission": privileges_to_permission(&member.privileges),
+                })
+            })
+            .collect();
+
+        let invites: Vec<_> = invites
+            .into_iter()
+            .map(|invite| {
+                json!({
+                    "id": invite.id,
+                    "email": invite.email,
+                    "permission": privileges_to_permission(&invite.privileges),
+                })
+            })
+            .collect();
+
+        print_json(&json!({ "members": members, "invites": invites }));
+        return Ok(());
+    }
+
+    println!("Members:");
+
+    for member in members {
+        let name = [member.first_name, member.last_name]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        println!(
+            "  {:<40}{:<12}{}",
+            member.email.green(),
+            privileges_to_permission(&member.privileges).cyan(),
+            name
+        );
+    }
+
+    if !invites.is_empty() {
+        println!("Pending invites:");
+
+        for invite in invites {
+            println!(
+                "  {:<40}{}",
+                invite.email.yellow(),
+                privileges_to_permission(&invite.privileges).cyan()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// Invite users to the project with given permission.
+async fn share_invite_action(emails: Vec<&String>, permission: &str) -> Result<()> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let project = get_project_info()?;
+    let privileges = permission_to_privileges(permission);
+
+    for email in &emails {
+        let mut spinner = OlSpinner::new(format!("Inviting {email}..."));
+
+        match overleaf_client
+            .invite_to_project(&project.id, email, privileges)
+            .await
+        {
+            Ok(()) => spinner.stop_with_success(format!("Invited {email} as {permission}.")),
+            Err(err) => {
+                spinner.stop_with_error(format!("Failed to invite {email}."));
+                return Err(err);
+            }
+        }
+    }
+
+    if json_output() {
+        print_json(&json!({ "invited": emails, "permission": permission }));
+    }
+
+    Ok(())
+}
+
+// Change permission of project member.
+async fn share_set_action(email: &String, permission: &str) -> Result<()> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let project = get_project_info()?;
+    let user_id = find_member_id(&overleaf_client, &project.id, email).await?;
+
+    overleaf_client
+        .set_member_privileges(&project.id, &user_id, permission_to_privileges(permission))
+        .await?;
+
+    success!("Changed permission of {email} to {permission}.");
+
+    if json_output() {
+        print_json(&json!({ "member": { "email": email, "permission": permission } }));
+    }
+
+    Ok(())
+}
+
+// Remove project member or revoke pending invite sent to given email.
+async fn share_rm_action(email: &String) -> Result<()> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let project = get_project_info()?;
+
+    let invite = overleaf_client
+        .get_project_invites(&project.id)
+        .await?
+        .into_iter()
+        .find(|invite| invite.email.eq_ignore_ascii_case(email));
+
+    if let Some(invite) = invite {
+        overleaf_client
+            .revoke_invite(&project.id, &invite.id)
+            .await?;
+
+        success!("Revoked invite sent to {email}.");
+
+        if json_output() {
+            print_json(&json!({ "revoked": email }));
+        }
+    } else {
+        let user_id = find_member_id(&overleaf_client, &project.id, email).await?;
+
+        overleaf_client.remove_member(&project.id, &user_id).await?;
+
+        success!("Removed {email} from the project.");
+
+        if json_output() {
+            print_json(&json!({ "removed": email }));
+        }
+    }
+
+    Ok(())
+}
+
+// Transfer ownership of the project to one of its members.
+async fn share_transfer_action(email: &String, force: &bool) -> Result<()> {
+    let message = format!(
+        "Transferring ownership to {email} cannot be undone by you. Do you want to continue?"
+    );
+    if confirm(&message, None, *force)? {
+        let session_info = session_info().await?;
+        let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+        let project = get_project_info()?;
+        let user_id = find_member_id(&overleaf_client, &project.id, email).await?;
+
+        overleaf_client
+            .transfer_ownership(&project.id, &user_id)
+            .await?;
+
+        success!("Transferred ownership of the project to {email}.");
+
+        if json_output() {
+            print_json(&json!({ "owner": email }));
+        }
+    }
+
+    Ok(())
+}
+
+// Turn link sharing on or off and print sharing links when enabled.
+async fn share_link_action(enabled: bool) -> Result<()> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let project = get_project_info()?;
+
+    overleaf_client
+        .set_link_sharing(&project.id, enabled)
+        .await?;
+
+    if !enabled {
+        success!("Turned off link sharing.");
+
+        if json_output() {
+            print_json(&json!({ "link_sharing": false }));
+        }
+
+        return Ok(());
+    }
+
+    success!("Turned on link sharing.");
+
+    let tokens = overleaf_client.get_share_tokens(&project.id).await?;
+
+    if json_output() {
+        print_json(&json!({
+            "link_sharing": true,
+            "read_only": tokens
+                .read_only
+                .map(|token| format!("{}/read/{token}", overleaf_client.url(BASE_URL))),
+            "read_write": tokens
+                .read_and_write
+                .map(|token| format!("{}/{token}", overleaf_client.url(BASE_URL))),
+        }));
+        return Ok(());
+    }
+
+    if let Some(token) = tokens.read_only {
+        println!(
+            "{:<12}{}",
+            "Read-only:",
+            format!("{}/read/{token}", overleaf_client.url(BASE_URL)).cyan()
+        );
+    }
+
+    if let Some(token) = tokens.read_and_write {
+        println!(
+            "{:<12}{}",
+            "Read-write:",
+            format!("{}/{token}", overleaf_client.url(BASE_URL)).cyan()
+        );
+    }
+
+    Ok(())
+}
+
+// Run operation for all projects matching filter and print summary. Fails if operation failed
+// for any project, otherwise returns number of processed projects.
+async fn batch_action(
+    operation: BatchOperation,
+    filter: ProjectFilter,
+    dir: &String,
+    jobs: &usize,
+    no_backup: &bool,
+) -> Result<usize> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let mut spinner = OlSpinner::new("Fetching list of projects...".to_owned());
+
+    let projects = match select_projects(&overleaf_client, &filter).await {
+        Ok(projects) => projects,
+        Err(err) => {
+            spinner.stop_with_error("Failed to fetch list of projects.".to_owned());
+            return Err(err);
+        }
+    };
+
+    spinner.stop_with_success(format!("Selected {} projects.", projects.len()));
+
+    if projects.is_empty() {
+        return Ok(0);
+    }
+
+    let target_dir = PathBuf::from(dir);
+    fs::create_dir_all(&target_dir)?;
+
+    let results = run_batch(
+        &overleaf_client,
+        operation,
+        projects,
+        &target_dir,
+        *jobs,
+        *no_backup,
+        &SpinnerProgress::default(),
+    )
+    .await;
+
+    print_batch_summary(&results);
+
+    let failed = results
+        .iter()
+        .filter(|batch_result| batch_result.result.is_err())
+        .count();
+
+    if failed > 0 {
+        bail!(
+            "Operation failed for {failed} of {} projects.",
+            results.len()
+        )
+    }
+
+    Ok(results.len())
+}
+
+// Download all projects changed since the previous run into archive directory. Returns numbers
+// of downloaded and skipped projects.
+async fn backup_all_action(dir: &String) -> Result<(usize, usize)> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let mut spinner = OlSpinner::new("Fetching list of projects...".to_owned());
+
+    let projects = match overleaf_client.get_all_projects().await {
+        Ok(projects_list) => projects_list.projects,
+        Err(err) => {
+            spinner.stop_with_error("Failed to fetch list of projects.".to_owned());
+            return Err(err);
+        }
+    };
+
+    spinner.stop_with_success(format!("Fetched list of {} projects.", projects.len()));
+
+    let archive_dir = PathBuf::from(dir);
+    fs::create_dir_all(&archive_dir)?;
+
+    archive_all_projects(
+        &overleaf_client,
+        projects,
+        &archive_dir,
+        &SpinnerProgress::default(),
+    )
+    .await
+}
+
+// Print remote projects matching filter.
+async fn list_action(filter: ProjectFilter) -> Result<()> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let projects = select_projects(&overleaf_client, &filter).await?;
+
+    if json_output() {
+        print_json(&json!({ "projects": projects }));
+        return Ok(());
+    }
+
+    if projects.is_empty() {
+        println!("No projects found.");
+    }
+
+    for project in projects {
+        println!(
+            "{}  {}  {}",
+            project.id.yellow(),
+            project
+                .last_updated
+                .as_deref()
+                .and_then(|date| chrono::DateTime::parse_from_rfc3339(date).ok())
+                .map(|date| format_timestamp(date.timestamp_millis()))
+                .unwrap_or_default(),
+            project.name.green()
+        );
+    }
+
+    Ok(())
+}
+
+// Find tag by its name.
+async fn find_tag(overleaf_client: &OverleafClient, name: &String) -> Result<Tag> {
+    overleaf_client
+        .get_all_tags()
+        .await?
+        .into_iter()
+        .find(|tag| tag.name == *name)
+        .context(format!("Tag {name} not found."))
+}
+
+// Print all tags with numbers of projects they contain.
+async fn tag_list_action() -> Result<()> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let tags = overleaf_client.get_all_tags().await?;
+
+    if json_output() {
+        print_json(&json!({ "tags": tags }));
+        return Ok(());
+    }
+
+    if tags.is_empty() {
+        println!("No tags found.");
+    }
+
+    for tag in tags {
+        println!("{}  ({} projects)", tag.name.green(), tag.project_ids.len());
+    }
+
+    Ok(())
+}
+
+// Create tag with given name.
+async fn tag_create_action(name: &String) -> Result<()> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let tag = overleaf_client.create_tag(name).await?;
+
+    success!("Created tag {name}.");
+
+    if json_output() {
+        print_json(&json!({ "tag": tag }));
+    }
+
+    Ok(())
+}
+
+// Rename tag.
+async fn tag_rename_action(name: &String, new_name: &String) -> Result<()> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let mut tag = find_tag(&overleaf_client, name).await?;
+    overleaf_client.rename_tag(&tag.id, new_name).await?;
+    tag.name = new_name.clone();
+
+    success!("Renamed tag {name} to {new_name}.");
+
+    if json_output() {
+        print_json(&json!({ "tag": tag }));
+    }
+
+    Ok(())
+}
+
+// Delete tag.
+async fn tag_rm_action(name: &String) -> Result<()> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let tag = find_tag(&overleaf_client, name).await?;
+    overleaf_client.delete_tag(&tag.id).await?;
+
+    success!("Deleted tag {name}.");
+
+    if json_output() {
+        print_json(&json!({ "removed": tag }));
+    }
+
+    Ok(())
+}
+
+// Add projects given by names or ids to tag, or remove them from it. If no projects are given,
+// the project of current repository is used.
+async fn tag_projects_action(name: &String, projects: Vec<&String>, add: bool) -> Result<()> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let tag = find_tag(&overleaf_client, name).await?;
+
+    let projects = if projects.is_empty() {
+        if !is_olsync_repository() {
+            bail!("Not a olsync repository! Specify projects to tag.")
+        }
+
+        vec![get_project_info()?]
+    } else {
+        let all_projects = overleaf_client.get_all_projects().await?.projects;
+
+        projects
+            .into_iter()
+            .map(|key| {
+                all_projects
+                    .iter()
+                    .find(|project| project.name == *key || project.id == *key)
+                    .cloned()
+                    .ok_or(anyhow!(OlsyncError::ProjectNotFound(key.clone())))
+            })
+            .collect::<Result<Vec<_>>>()?
+    };
+
+    for project in &projects {
+        if add {
+            overleaf_client
+                .add_project_to_tag(&tag.id, &project.id)
+                .await?;
+            success!("Added project {} to tag {name}.", project.name);
+        } else {
+            overleaf_client
+                .remove_project_from_tag(&tag.id, &project.id)
+                .await?;
+            success!("Removed project {} from tag {name}.", project.name);
+        }
+    }
+
+    if json_output() {
+        let key = if add { "added" } else { "removed" };
+        print_json(&json!({ "tag": tag.name, key: projects }));
+    }
+
+    Ok(())
+}
+
+// Push local changes to remote project as they happen.
+async fn watch_action(debounce: &u64) -> Result<()> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let project = get_project_info()?;
+    let repo_root = get_repo_root()?;
+
+    watch_repository(
+        &overleaf_client,
+        &project.id,
+        &repo_root,
+        Duration::from_millis(*debounce),
+        &|event| print_sync_event(&event),
+    )
+    .await
+}
+
+// Pull changes of collaborators into local repository as they happen.
+async fn follow_action() -> Result<()> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let project = get_project_info()?;
+    let repo_root = get_repo_root()?;
+
+    follow_project_changes(&overleaf_client, &project.id, &repo_root, &|event| {
+        print_sync_event(&event)
+    })
+    .await
+}
+
+// Run daemon holding session and followed projects for any number of repositories.
+async fn daemon_action(socket: Option<&String>) -> Result<()> {
+    let session_info = session_info().await?;
+    let overleaf_client = OverleafClient::new(session_info, &current_settings().instance_url)?;
+
+    let socket_path = match socket {
+        Some(path) => PathBuf::from(path),
+        None => default_socket_path()?,
+    };
+
+    run_daemon(overleaf_client, &socket_path).await
+}
+
+// Format size in bytes with binary unit, e.g. 1.5 MiB.
+fn format_size(size: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut size = size as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{size} {}", units[unit])
+    } else {
+        format!("{size:.1} {}", units[unit])
+    }
+}
+
+// List backups of current repository, the newest first.
+fn backup_list_action() -> Result<()> {
+    let backups = list_backups(&get_repo_root()?)?;
+
+    if json_output() {
+        print_json(&json!({ "backups": backups }));
+        return Ok(());
+    }
+
+    if backups.is_empty() {
+        println!("No backups found.");
+    }
+
+    for backup in backups {
+        println!(
+            "{}  {}  {:>10}",
+            backup.id.yellow(),
+            format_timestamp(backup.timestamp),
+            format_size(backup.size)
+        );
+    }
+
+    Ok(())
+}
+
+// Override local state with backup of given id, after backing up the current one.
+fn backup_restore_action(id: &str, force: bool) -> Result<()> {
+    let ans = confirm(
+        &format!("Restoring backup {id} will override your local state. Do you want to continue?"),
+        Some("If you proceed, your local project will be backed up first."),
+        force,
+    )?;
+
+    if !ans {
+        return Ok(());
+    }
+
+    let safety_backup = restore_backup(&get_repo_root()?, id, &SpinnerProgress::default())?;
+
+    if json_output() {
+        print_json(&json!({ "restored": id, "backup": safety_backup }));
+    } else {
+        success!("Restored backup {id}.");
+    }
+
+    Ok(())
+}
+
+// Remove backups beyond given number of the newest ones or older than given age.
+fn backup_prune_action(
+    keep: Option<usize>,
+    older_than: Option<&String>,
+    force: bool,
+) -> Result<()> {
+    let max_age = older_than.map(|age| parse_age(age)).transpose()?;
+
+    let ans = confirm(
+        "Pruning will permanently remove old backups. Do you want to continue?",
+        None,
+        force,
+    )?;
+
+    if !ans {
+        return Ok(());
+    }
+
+    let removed = prune_backups(&get_repo_root()?, keep, max_age)?;
+
+    if json_output() {
+        print_json(&json!({ "removed": removed }));
+    } else {
+        success!("Removed {} backups.", removed.len());
+    }
+
+    Ok(())
+}
+
+// Print setting in a form which can be passed back to olsync config set. Strings are printed
+// without quotes.
+fn format_setting(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(string) => string.clone(),
+        value => value.to_string(),
+    }
+}
+
+// Print value of setting, taking into account both global and repository config.
+fn config_get_action(key: &str) -> Result<()> {
+    let value = current_settings().get(key)?;
+
+    if json_output() {
+        print_json(&json!({ "key": key, "value": value }));
+    } else if let Some(value) = value {
+        println!("{}", format_setting(&value));
+    }
+
+    Ok(())
+}
+
+// Change setting in config of current repository, or in global config.
+fn config_set_action(key: &str, value: &str, global: bool) -> Result<()> {
+    let config_path = if global {
+        global_config_path()?
+    } else {
+        let repo_root = get_repo_root().map_err(|_| {
+            anyhow!("Not a olsync repository! Use --global to change global config.")
+        })?;
+
+        repo_config_path(&repo_root)
+    };
+
+    set_config_value(&config_path, key, value)?;
+
+    success!("Set {key} to {value} in {}.", path_to_str(&config_path));
+
+    if json_output() {
+        print_json(&json!({ "key": key, "value": value, "path": config_path }));
+    }
+
+    Ok(())
+}
+
+// Print all settings, with defaults for the ones not set in any config.
+fn config_list_action() -> Result<()> {
+    let settings = current_settings();
+
+    if json_output() {
+        print_json(&json!({ "settings": settings }));
+        return Ok(());
+    }
+
+    for key in CONFIG_KEYS {
+        match settings.get(key)? {
+            Some(value) => println!("{} = {}", key.green(), value),
+            None => println!("{} = {}", key.green(), "not set".dimmed()),
+        }
+    }
+
+    Ok(())
 }
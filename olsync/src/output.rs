@@ -0,0 +1,39 @@
+use olsync_core::error::ErrorKind;
+use serde::Serialize;
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+// Switch to machine-readable output. Results of commands are then printed on stdout as JSON
+// objects, one per line, while spinners are suppressed and human-readable messages are printed
+// on stderr.
+pub fn enable_json_output() {
+    JSON_OUTPUT.store(true, Ordering::Relaxed);
+}
+
+pub fn json_output() -> bool {
+    JSON_OUTPUT.load(Ordering::Relaxed)
+}
+
+// Print value as a single line of JSON on stdout.
+pub fn print_json<T: Serialize>(value: &T) {
+    match serde_json::to_string(value) {
+        Ok(line) => println!("{line}"),
+        Err(err) => eprintln!("Failed to serialize output: {err}"),
+    }
+}
+
+// Print error together with its kind and the exit code of olsync.
+pub fn print_json_error(error: &anyhow::Error) {
+    let kind = ErrorKind::of(error);
+
+    print_json(&json!({
+        "success": false,
+        "error": {
+            "kind": kind,
+            "exit_code": kind.exit_code(),
+            "message": error.to_string(),
+        },
+    }));
+}
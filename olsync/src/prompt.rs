@@ -0,0 +1,60 @@
+use anyhow::{anyhow, bail, Result};
+use std::{
+    io::{stdin, IsTerminal},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+static NON_INTERACTIVE: AtomicBool = AtomicBool::new(false);
+
+// Answer all confirm prompts with yes.
+pub fn assume_yes() {
+    ASSUME_YES.store(true, Ordering::Relaxed);
+}
+
+// Never prompt, even if stdin is a terminal.
+pub fn disable_prompts() {
+    NON_INTERACTIVE.store(true, Ordering::Relaxed);
+}
+
+// Whether user can be asked for input. It is not possible when stdin is not a terminal, e.g. in
+// CI or when olsync is run by another program.
+pub fn is_interactive() -> bool {
+    !NON_INTERACTIVE.load(Ordering::Relaxed) && stdin().is_terminal()
+}
+
+// Ask user to confirm action, unless it has been confirmed with a flag. Fails if user cannot be
+// asked, since going on without confirmation could override their files.
+pub fn confirm(message: &str, help_message: Option<&str>, confirmed: bool) -> Result<bool> {
+    if confirmed || ASSUME_YES.load(Ordering::Relaxed) {
+        return Ok(true);
+    }
+
+    if !is_interactive() {
+        bail!(
+            "Cannot ask for confirmation in non-interactive mode: {message} Use --yes to confirm."
+        );
+    }
+
+    let mut prompt = inquire::Confirm::new(message).with_default(false);
+
+    if let Some(help_message) = help_message {
+        prompt = prompt.with_help_message(help_message);
+    }
+
+    prompt
+        .prompt()
+        .map_err(|e| anyhow!("An error ocurred in prompt: {e}"))
+}
+
+// Ask user to select one of options. Fails with given hint on how to specify the choice with
+// arguments if user cannot be asked.
+pub fn select(message: &str, options: Vec<String>, hint: &str) -> Result<String> {
+    if !is_interactive() {
+        bail!("Cannot prompt for selection in non-interactive mode. {hint}");
+    }
+
+    inquire::Select::new(message, options)
+        .prompt()
+        .map_err(|e| anyhow!("An error ocurred in prompt: {e}"))
+}
@@ -0,0 +1,534 @@
+// End-to-end tests running olsync binary against local mock of Overleaf.
+
+use olsync_core::utils::path_to_str;
+use olsync_mock::MockOverleaf;
+use serde_json::{json, Value};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+use tempfile::TempDir;
+
+const PROJECT_NAME: &str = "thesis";
+
+struct TestEnv {
+    dir: TempDir,
+    server: MockOverleaf,
+}
+
+impl TestEnv {
+    // Start mock server with a single project and log in by writing cached session info.
+    fn new() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+
+        for sub_dir in ["server", "home", "olsync", "work"] {
+            fs::create_dir(dir.path().join(sub_dir)).unwrap();
+        }
+
+        let env = TestEnv {
+            server: MockOverleaf::start(dir.path().join("server"), "127.0.0.1:0".parse().unwrap())
+                .unwrap(),
+            dir,
+        };
+
+        env.write_remote(
+            "main.tex",
+            b"\\documentclass{article}\n\\input{chapters/intro}\n",
+        );
+        env.write_remote("chapters/intro.tex", "Zażółć gęślą jaźń.\n".as_bytes());
+        env.write_remote("figures/logo.png", &[0x89, 0x50, 0x4e, 0x47, 0xff, 0x00]);
+
+        env.write_session("overleaf_session2");
+
+        env
+    }
+
+    // Write cached session info with session cookie of given name. Mock server accepts only
+    // overleaf_session2 cookie.
+    fn write_session(&self, session_cookie_name: &str) {
+        fs::write(
+            self.olsync_home().join("session.json"),
+            session_info(session_cookie_name),
+        )
+        .unwrap();
+    }
+
+    fn home(&self) -> PathBuf {
+        self.dir.path().join("home")
+    }
+
+    // Directory of global files of olsync, passed in OLSYNC_HOME.
+    fn olsync_home(&self) -> PathBuf {
+        self.dir.path().join("olsync")
+    }
+
+    fn work(&self) -> PathBuf {
+        self.dir.path().join("work")
+    }
+
+    fn repo(&self) -> PathBuf {
+        self.work().join(PROJECT_NAME)
+    }
+
+    fn remote_path(&self, path: &str) -> PathBuf {
+        self.dir.path().join("server").join(PROJECT_NAME).join(path)
+    }
+
+    fn write_remote(&self, path: &str, content: &[u8]) {
+        write_file(&self.remote_path(path), content);
+    }
+
+    fn read_remote(&self, path: &str) -> Vec<u8> {
+        fs::read(self.remote_path(path)).unwrap()
+    }
+
+    // Run olsync with given arguments in given directory.
+    fn olsync(&self, dir: &Path, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_olsync"))
+            .args(args)
+            .current_dir(dir)
+            .env("HOME", self.home())
+            .env("OLSYNC_HOME", self.olsync_home())
+            .env("OLSYNC_BASE_URL", self.server.base_url())
+            .output()
+            .unwrap()
+    }
+
+    fn clone_project(&self) {
+        assert_success(self.olsync(&self.work(), &["clone", "--name", PROJECT_NAME]));
+    }
+}
+
+// Serialized session info with session cookie of given name.
+fn session_info(session_cookie_name: &str) -> String {
+    let cookie = |name: &str| json!({ "name": name, "value": "mock", "expires": 4102444800.0 });
+
+    json!({
+        "email": olsync_mock::MOCK_EMAIL,
+        "session_cookie": cookie(session_cookie_name),
+        "gclb_cookie": cookie("GCLB"),
+        "csrf_token": olsync_mock::MOCK_CSRF_TOKEN,
+    })
+    .to_string()
+}
+
+fn write_file(path: &Path, content: &[u8]) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+fn assert_success(output: Output) {
+    assert!(
+        output.status.success(),
+        "olsync failed\nstdout:\n{}\nstderr:\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+// Parse stdout consisting of a single line of JSON.
+fn parse_json_line(output: &Output) -> Value {
+    let stdout = String::from_utf8(output.stdout.clone()).unwrap();
+    assert_eq!(stdout.lines().count(), 1, "unexpected stdout:\n{stdout}");
+    serde_json::from_str(&stdout).unwrap()
+}
+
+// Push and pulling single files need project details, which are fetched through socket.io
+// client. Tests depending on it are skipped when the client is not installed.
+fn socketio_client_installed() -> bool {
+    let installed = Command::new("olsync-rs-socketio-client")
+        .arg("--help")
+        .output()
+        .is_ok_and(|output| output.status.success());
+
+    if !installed {
+        eprintln!("olsync-rs-socketio-client is not installed, skipping test.");
+    }
+
+    installed
+}
+
+#[test]
+fn clone_downloads_project() {
+    let env = TestEnv::new();
+
+    env.clone_project();
+
+    for path in ["main.tex", "chapters/intro.tex", "figures/logo.png"] {
+        assert_eq!(
+            fs::read(env.repo().join(path)).unwrap(),
+            env.read_remote(path)
+        );
+    }
+
+    assert!(env.repo().join(".olsync").join("projectinfo").exists());
+    assert!(env.repo().join(".olsync").join("lastpull").exists());
+}
+
+#[test]
+fn clone_fails_for_unknown_project() {
+    let env = TestEnv::new();
+
+    let output = env.olsync(&env.work(), &["clone", "--name", "unknown"]);
+
+    assert_eq!(output.status.code(), Some(4));
+    assert!(!env.work().join("unknown").exists());
+}
+
+#[test]
+fn clone_fails_with_invalid_session() {
+    let env = TestEnv::new();
+    env.write_session("invalid_session");
+
+    let output = env.olsync(&env.work(), &["clone", "--name", PROJECT_NAME]);
+
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn clone_fails_if_directory_exists() {
+    let env = TestEnv::new();
+    fs::create_dir(env.repo()).unwrap();
+
+    let output = env.olsync(&env.work(), &["clone", "--name", PROJECT_NAME]);
+
+    assert_eq!(output.status.code(), Some(6));
+}
+
+#[test]
+fn json_output_contains_only_json() {
+    let env = TestEnv::new();
+
+    let output = env.olsync(&env.work(), &["--json", "clone", "--name", PROJECT_NAME]);
+    assert!(output.status.success());
+
+    let result = parse_json_line(&output);
+    assert_eq!(result["project"]["name"], PROJECT_NAME);
+    assert_eq!(result["path"], path_to_str(&env.repo()));
+
+    let output = env.olsync(&env.work(), &["list", "--json"]);
+    assert_eq!(
+        parse_json_line(&output)["projects"][0]["name"],
+        PROJECT_NAME
+    );
+
+    let output = env.olsync(&env.repo(), &["pull", "--force", "--json"]);
+    assert!(output.status.success());
+    assert_eq!(parse_json_line(&output)["project"]["name"], PROJECT_NAME);
+
+    let output = env.olsync(
+        &env.repo(),
+        &["config", "set", "confirm", "false", "--json"],
+    );
+    assert!(output.status.success());
+    assert_eq!(parse_json_line(&output)["key"], "confirm");
+}
+
+#[test]
+fn json_output_reports_errors() {
+    let env = TestEnv::new();
+
+    let output = env.olsync(&env.work(), &["--json", "clone", "--name", "unknown"]);
+
+    assert_eq!(output.status.code(), Some(4));
+
+    let error = &parse_json_line(&output)["error"];
+    assert_eq!(error["kind"], "project_not_found");
+    assert_eq!(error["exit_code"], 4);
+}
+
+#[test]
+fn pull_replaces_local_state_and_keeps_backup() {
+    let env = TestEnv::new();
+    env.clone_project();
+
+    env.write_remote("main.tex", b"remote change\n");
+    write_file(&env.repo().join("notes.txt"), b"local only\n");
+
+    assert_success(env.olsync(&env.repo(), &["pull", "--force"]));
+
+    assert_eq!(
+        fs::read(env.repo().join("main.tex")).unwrap(),
+        b"remote change\n"
+    );
+    assert!(!env.repo().join("notes.txt").exists());
+
+    let backups: Vec<_> = fs::read_dir(env.repo().join(".olsync"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.to_string_lossy().ends_with(".local.bak"))
+        .collect();
+
+    assert_eq!(backups.len(), 1);
+    assert!(backups[0].join("notes.txt").exists());
+}
+
+#[test]
+fn pull_with_expired_session_keeps_local_state() {
+    let env = TestEnv::new();
+    env.clone_project();
+
+    write_file(&env.repo().join("notes.txt"), b"local only\n");
+    env.write_session("expired_session");
+
+    let output = env.olsync(&env.repo(), &["pull", "--force", "--no-backup"]);
+
+    assert_eq!(output.status.code(), Some(3));
+    assert!(env.repo().join("notes.txt").exists());
+    assert!(env.repo().join("main.tex").exists());
+}
+
+#[test]
+fn confirmation_is_required_without_terminal() {
+    let env = TestEnv::new();
+    env.clone_project();
+
+    env.write_remote("main.tex", b"remote change\n");
+
+    let output = env.olsync(&env.repo(), &["pull"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--yes"));
+    assert_ne!(
+        fs::read(env.repo().join("main.tex")).unwrap(),
+        b"remote change\n"
+    );
+
+    assert_success(env.olsync(&env.repo(), &["pull", "--yes"]));
+
+    assert_eq!(
+        fs::read(env.repo().join("main.tex")).unwrap(),
+        b"remote change\n"
+    );
+}
+
+#[test]
+fn config_settings_change_pull() {
+    let env = TestEnv::new();
+    env.clone_project();
+
+    assert_success(env.olsync(&env.repo(), &["config", "set", "confirm", "false"]));
+    assert_success(env.olsync(&env.repo(), &["config", "set", "backup.enabled", "false"]));
+    assert_success(env.olsync(&env.repo(), &["config", "set", "remote_dir", "chapters"]));
+
+    let output = env.olsync(&env.repo(), &["config", "get", "remote_dir"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "chapters\n");
+
+    let output = env.olsync(
+        &env.repo(),
+        &["config", "set", "backup.enabled", "sometimes"],
+    );
+    assert!(!output.status.success());
+
+    assert_success(env.olsync(&env.repo(), &["pull"]));
+
+    assert_eq!(
+        fs::read(env.repo().join("intro.tex")).unwrap(),
+        env.read_remote("chapters/intro.tex")
+    );
+    assert!(!env.repo().join("main.tex").exists());
+
+    let backups = fs::read_dir(env.repo().join(".olsync"))
+        .unwrap()
+        .filter(|entry| {
+            let path = entry.as_ref().unwrap().path();
+            path.to_string_lossy().ends_with(".local.bak")
+        })
+        .count();
+
+    assert_eq!(backups, 0);
+
+    let config_path = env.repo().join(".olsync").join("config");
+    let config = fs::read_to_string(&config_path).unwrap();
+    write_file(
+        &config_path,
+        format!("confrim = false\n{config}").as_bytes(),
+    );
+
+    assert!(!env.olsync(&env.repo(), &["pull"]).status.success());
+    assert_success(env.olsync(&env.repo(), &["config", "list"]));
+    assert_success(env.olsync(
+        &env.repo(),
+        &["config", "set", "--global", "confirm", "false"],
+    ));
+}
+
+#[test]
+fn backups_can_be_restored_and_pruned() {
+    let env = TestEnv::new();
+    env.clone_project();
+
+    write_file(&env.repo().join("notes.txt"), b"local only\n");
+    assert_success(env.olsync(&env.repo(), &["pull", "--yes"]));
+    assert!(!env.repo().join("notes.txt").exists());
+
+    let output = env.olsync(&env.repo(), &["backup", "list", "--json"]);
+    let backups = parse_json_line(&output)["backups"].clone();
+    assert_eq!(backups.as_array().unwrap().len(), 1);
+
+    let id = backups[0]["id"].as_str().unwrap();
+    assert_success(env.olsync(&env.repo(), &["backup", "restore", id, "--yes"]));
+    assert!(env.repo().join("notes.txt").exists());
+
+    let output = env.olsync(&env.repo(), &["backup", "list", "--json"]);
+    assert_eq!(
+        parse_json_line(&output)["backups"]
+            .as_array()
+            .unwrap()
+            .len(),
+        2
+    );
+
+    let output = env.olsync(
+        &env.repo(),
+        &["backup", "prune", "--keep", "1", "--yes", "--json"],
+    );
+    assert_eq!(parse_json_line(&output)["removed"][0]["id"], id);
+
+    assert_success(env.olsync(&env.repo(), &["config", "set", "backup.max_age", "0s"]));
+    assert_success(env.olsync(&env.repo(), &["pull", "--yes"]));
+
+    let output = env.olsync(&env.repo(), &["backup", "list", "--json"]);
+    assert_eq!(
+        parse_json_line(&output)["backups"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn clone_without_project_fails_without_terminal() {
+    let env = TestEnv::new();
+
+    let output = env.olsync(&env.work(), &["clone"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--name"));
+}
+
+#[test]
+fn missing_session_fails_without_terminal() {
+    let env = TestEnv::new();
+    fs::remove_file(env.olsync_home().join("session.json")).unwrap();
+
+    let output = env.olsync(&env.work(), &["list"]);
+
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn legacy_session_is_moved_to_state_directory() {
+    let env = TestEnv::new();
+    let state_home = env.dir.path().join("state");
+
+    fs::write(
+        env.home().join(".olsyncinfo"),
+        session_info("overleaf_session2"),
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_olsync"))
+        .args(["whoami", "--json"])
+        .current_dir(env.work())
+        .env("HOME", env.home())
+        .env("XDG_CONFIG_HOME", env.dir.path().join("config"))
+        .env("XDG_STATE_HOME", &state_home)
+        .env_remove("OLSYNC_HOME")
+        .output()
+        .unwrap();
+
+    assert_eq!(parse_json_line(&output)["email"], olsync_mock::MOCK_EMAIL);
+    assert!(state_home.join("olsync").join("session.json").exists());
+    assert!(!env.home().join(".olsyncinfo").exists());
+}
+
+#[test]
+fn log_file_traces_requests_without_secrets() {
+    let env = TestEnv::new();
+    let log_file = env.work().join("olsync.log");
+
+    let output = env.olsync(
+        &env.work(),
+        &[
+            "clone",
+            "--name",
+            PROJECT_NAME,
+            "--log-file",
+            path_to_str(&log_file),
+        ],
+    );
+    assert_success(output);
+
+    let log = fs::read_to_string(&log_file).unwrap();
+    assert!(log.contains("--> GET "));
+    assert!(!log.contains("overleaf_session2=mock"));
+}
+
+#[test]
+fn pull_outside_repository_fails() {
+    let env = TestEnv::new();
+
+    let output = env.olsync(&env.work(), &["pull", "--force"]);
+
+    assert!(!output.status.success());
+}
+
+#[test]
+fn push_updates_docs_and_uploads_new_files() {
+    if !socketio_client_installed() {
+        return;
+    }
+
+    let env = TestEnv::new();
+    env.clone_project();
+
+    write_file(
+        &env.repo().join("chapters/intro.tex"),
+        "Zażółć gęślą jaźń!\nNew line.\n".as_bytes(),
+    );
+    write_file(&env.repo().join("appendix/extra.tex"), b"Appendix\n");
+
+    assert_success(env.olsync(
+        &env.repo(),
+        &[
+            "push",
+            "--force",
+            "chapters/intro.tex",
+            "appendix/extra.tex",
+        ],
+    ));
+
+    assert_eq!(
+        env.read_remote("chapters/intro.tex"),
+        "Zażółć gęślą jaźń!\nNew line.\n".as_bytes()
+    );
+    assert_eq!(env.read_remote("appendix/extra.tex"), b"Appendix\n");
+}
+
+#[test]
+fn pull_files_leaves_other_files_untouched() {
+    if !socketio_client_installed() {
+        return;
+    }
+
+    let env = TestEnv::new();
+    env.clone_project();
+
+    env.write_remote("main.tex", b"remote main\n");
+    env.write_remote("chapters/intro.tex", b"remote intro\n");
+    write_file(&env.repo().join("main.tex"), b"local main\n");
+
+    assert_success(env.olsync(&env.repo(), &["pull", "--force", "--no-backup", "chapters"]));
+
+    assert_eq!(
+        fs::read(env.repo().join("main.tex")).unwrap(),
+        b"local main\n"
+    );
+    assert_eq!(
+        fs::read(env.repo().join("chapters/intro.tex")).unwrap(),
+        b"remote intro\n"
+    );
+}
@@ -1,25 +1,21 @@
 # This solution is taken from https://github.com/zzjjzzgggg/overleaf-sync/blob/887fdd8e5709ca3f02fc29973ece84dbfe6f9430/olsync/olsync/olclient.py#L136
 
+import os
+import sys
+import json
 import time
+import difflib
 import argparse
 from socketIO_client import SocketIO
 
-BASE_URL = "https://www.overleaf.com"
+# Base URL can be overridden the same way as in olsync, e.g. to use a local mock server in tests.
+BASE_URL = os.environ.get("OLSYNC_BASE_URL") or "https://www.overleaf.com"
 
+# How long to wait for the server to acknowledge joinDoc and applyOtUpdate, in seconds.
+CALLBACK_TIMEOUT = 30
 
-def main():
-    parser = argparse.ArgumentParser(description="overleaf-sync-rs socket.io client")
-
-    parser.add_argument("GCLB", help="GCLB cookie value.")
-    parser.add_argument("overleaf_session2", help="overleaf_session2 cookie value.")
-    parser.add_argument("project_id", help="The id of Overleaf project for which data is fetched.")
-
-    args = parser.parse_args()
-
-    GCLB = args.GCLB
-    overleaf_session_2 = args.overleaf_session2
-    project_id = args.project_id
 
+def connect(GCLB, overleaf_session_2, project_id):
     project_infos = None
 
     def set_project_infos(project_infos_dict):
@@ -43,10 +39,207 @@ def main():
     while project_infos is None:
         socket_io.wait(1)
 
-    if socket_io.connected:
-        socket_io.disconnect()
+    return socket_io, project_infos
+
+
+def utf16_length(text):
+    # Overleaf measures op positions in JavaScript string units, i.e. UTF-16 code units.
+    return len(text.encode('utf-16-le')) // 2
+
+
+def diff_ops(old_text, new_text):
+    # Compute ShareJS text ops turning old_text into new_text. Positions of ops refer to the
+    # document with all previous ops already applied.
+    ops = []
+    offset = 0
+
+    matcher = difflib.SequenceMatcher(None, old_text, new_text, autojunk=False)
+
+    for tag, i1, i2, j1, j2 in matcher.get_opcodes():
+        if tag == 'equal':
+            offset += utf16_length(old_text[i1:i2])
+            continue
+
+        if tag in ('delete', 'replace'):
+            ops.append({'p': offset, 'd': old_text[i1:i2]})
+
+        if tag in ('insert', 'replace'):
+            ops.append({'p': offset, 'i': new_text[j1:j2]})
+            offset += utf16_length(new_text[j1:j2])
+
+    return ops
+
+
+def join_doc(socket_io, doc_id):
+    # Join doc to receive its updates. Returns current text and version of the doc.
+    joined = None
+
+    def on_join_doc(error, lines=None, version=None, *args):
+        nonlocal joined
+        joined = (error, lines, version)
+
+    socket_io.emit('joinDoc', doc_id, {'encodeRanges': True}, on_join_doc)
+    socket_io.wait_for_callbacks(seconds=CALLBACK_TIMEOUT)
+
+    if joined is None:
+        raise RuntimeError("Timed out while joining doc {}.".format(doc_id))
+
+    error, lines, version = joined
+
+    if error:
+        raise RuntimeError("Failed to join doc {}: {}.".format(doc_id, error))
+
+    # Lines of joined doc are sent as UTF-8 bytes escaped into a latin-1 string.
+    text = "\n".join(line.encode('latin-1').decode('utf-8') for line in lines)
+
+    return text, version
+
+
+def update_doc(socket_io, doc_id, new_text):
+    old_text, version = join_doc(socket_io, doc_id)
+
+    ops = diff_ops(old_text, new_text)
+
+    if ops:
+        applied = None
+
+        def on_apply(error=None, *args):
+            nonlocal applied
+            applied = error or False
+
+        socket_io.emit('applyOtUpdate', doc_id, {'doc': doc_id, 'op': ops, 'v': version}, on_apply)
+        socket_io.wait_for_callbacks(seconds=CALLBACK_TIMEOUT)
+
+        if applied is None:
+            raise RuntimeError("Timed out while updating doc {}.".format(doc_id))
+
+        if applied:
+            raise RuntimeError("Failed to update doc {}: {}.".format(doc_id, applied))
+
+    socket_io.emit('leaveDoc', doc_id)
+
+
+def apply_ops(text, ops):
+    # Apply ShareJS text ops to text. Positions of ops are in UTF-16 code units.
+    encoded = text.encode('utf-16-le')
+
+    for op in ops:
+        position = op['p'] * 2
+
+        if 'i' in op:
+            encoded = encoded[:position] + op['i'].encode('utf-16-le') + encoded[position:]
+        elif 'd' in op:
+            encoded = encoded[:position] + encoded[position + len(op['d'].encode('utf-16-le')):]
+
+    return encoded.decode('utf-16-le')
+
+
+def collect_doc_ids(folder):
+    doc_ids = [doc['_id'] for doc in folder.get('docs', [])]
+
+    for subfolder in folder.get('folders', []):
+        doc_ids.extend(collect_doc_ids(subfolder))
+
+    return doc_ids
+
+
+def emit_event(event, **data):
+    print(json.dumps(dict(event=event, **data)), flush=True)
+
+
+def follow(socket_io, project_infos):
+    # Print changes of the project as JSON lines until the connection is closed.
+    docs = {}
+    pending_doc_ids = []
+
+    for folder in project_infos.get('rootFolder', []):
+        pending_doc_ids.extend(collect_doc_ids(folder))
+
+    def on_update_applied(update):
+        doc_id = update.get('doc')
+
+        if doc_id not in docs or 'op' not in update:
+            return
+
+        text, version = docs[doc_id]
+
+        # Missed update, the doc is joined again to get its current state.
+        if update.get('v') != version:
+            docs.pop(doc_id)
+            pending_doc_ids.append(doc_id)
+            return
+
+        text = apply_ops(text, update['op'])
+        docs[doc_id] = (text, version + 1)
+
+        emit_event('docUpdated', docId=doc_id, content=text)
+
+    def on_new_doc(folder_id, doc, *args):
+        emit_event('newDoc', folderId=folder_id, doc=doc)
+        pending_doc_ids.append(doc['_id'])
+
+    def on_remove_entity(entity_id, *args):
+        docs.pop(entity_id, None)
+        emit_event('remove', entityId=entity_id)
+
+    socket_io.on('otUpdateApplied', on_update_applied)
+    socket_io.on('reciveNewDoc', on_new_doc)
+    socket_io.on('reciveNewFile',
+                 lambda folder_id, file, *args: emit_event('newFile', folderId=folder_id, file=file))
+    socket_io.on('reciveNewFolder',
+                 lambda folder_id, folder, *args: emit_event('newFolder', folderId=folder_id, folder=folder))
+    socket_io.on('reciveEntityRename',
+                 lambda entity_id, name, *args: emit_event('rename', entityId=entity_id, newName=name))
+    socket_io.on('reciveEntityMove',
+                 lambda entity_id, folder_id, *args: emit_event('move', entityId=entity_id, folderId=folder_id))
+    socket_io.on('removeEntity', on_remove_entity)
+
+    emit_event('joined', project=project_infos)
+
+    while socket_io.connected:
+        while pending_doc_ids:
+            doc_id = pending_doc_ids.pop(0)
+            text, version = join_doc(socket_io, doc_id)
+            docs[doc_id] = (text, version)
+
+            emit_event('docJoined', docId=doc_id, content=text)
+
+        socket_io.wait(seconds=1)
+
+
+def main():
+    parser = argparse.ArgumentParser(description="overleaf-sync-rs socket.io client")
+
+    parser.add_argument("GCLB", help="GCLB cookie value.")
+    parser.add_argument("overleaf_session2", help="overleaf_session2 cookie value.")
+    parser.add_argument("project_id", help="The id of Overleaf project for which data is fetched.")
+    parser.add_argument("--update-doc", metavar="DOC_ID",
+                        help="Replace content of the doc with text read from stdin instead of "
+                             "printing project data.")
+    parser.add_argument("--follow", action="store_true",
+                        help="Print changes of the project as JSON lines instead of printing "
+                             "project data.")
+
+    args = parser.parse_args()
+
+    new_text = sys.stdin.buffer.read().decode('utf-8') if args.update_doc else None
+
+    socket_io, project_infos = connect(args.GCLB, args.overleaf_session2, args.project_id)
+
+    try:
+        if args.update_doc:
+            update_doc(socket_io, args.update_doc, new_text)
+        elif args.follow:
+            follow(socket_io, project_infos)
+    except RuntimeError as e:
+        print(e, file=sys.stderr)
+        sys.exit(1)
+    finally:
+        if socket_io.connected:
+            socket_io.disconnect()
 
-    print(project_infos)
+    if not args.update_doc and not args.follow:
+        print(project_infos)
 
 if __name__ == "__main__":
     main()